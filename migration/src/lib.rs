@@ -2,6 +2,17 @@ pub use sea_orm_migration::prelude::*;
 
 mod m20250611_142906_create_board_data_table;
 mod m20250612_035646_create_vote;
+mod m20261018_000001_create_vote_history;
+mod m20261018_000002_create_guild_settings;
+mod m20261018_000003_add_board_chart_style;
+mod m20261018_000004_add_vote_eligibility;
+mod m20261018_000005_add_audit_log_channel;
+mod m20261018_000006_create_vote_report;
+mod m20261018_000007_add_report_photo;
+mod m20261018_000008_add_reputation;
+mod m20261018_000009_create_voter_ban;
+mod m20261018_000010_add_report_photo_message;
+mod m20261018_000011_add_report_hidden_photo;
 
 pub struct Migrator;
 
@@ -11,6 +22,17 @@ impl MigratorTrait for Migrator {
         vec![
             Box::new(m20250611_142906_create_board_data_table::Migration),
             Box::new(m20250612_035646_create_vote::Migration),
+            Box::new(m20261018_000001_create_vote_history::Migration),
+            Box::new(m20261018_000002_create_guild_settings::Migration),
+            Box::new(m20261018_000003_add_board_chart_style::Migration),
+            Box::new(m20261018_000004_add_vote_eligibility::Migration),
+            Box::new(m20261018_000005_add_audit_log_channel::Migration),
+            Box::new(m20261018_000006_create_vote_report::Migration),
+            Box::new(m20261018_000007_add_report_photo::Migration),
+            Box::new(m20261018_000008_add_reputation::Migration),
+            Box::new(m20261018_000009_create_voter_ban::Migration),
+            Box::new(m20261018_000010_add_report_photo_message::Migration),
+            Box::new(m20261018_000011_add_report_hidden_photo::Migration),
         ]
     }
 }
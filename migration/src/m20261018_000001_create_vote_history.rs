@@ -0,0 +1,66 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(VoteHistory::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(VoteHistory::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(VoteHistory::UserId)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(VoteHistory::GuildId).big_integer().null())
+                    .col(ColumnDef::new(VoteHistory::Action).string().not_null())
+                    .col(ColumnDef::new(VoteHistory::PeriodDate).date().not_null())
+                    .col(
+                        ColumnDef::new(VoteHistory::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_vote_history_period_date")
+                    .table(VoteHistory::Table)
+                    .col(VoteHistory::PeriodDate)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(VoteHistory::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum VoteHistory {
+    Table,
+    Id,
+    UserId,
+    GuildId,
+    Action,
+    PeriodDate,
+    CreatedAt,
+}
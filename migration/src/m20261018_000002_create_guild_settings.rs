@@ -0,0 +1,88 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(GuildSettings::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(GuildSettings::ServerId)
+                            .big_integer()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(GuildSettings::ChartTheme)
+                            .string()
+                            .not_null()
+                            .default("light"),
+                    )
+                    .col(
+                        ColumnDef::new(GuildSettings::ChartLocale)
+                            .string()
+                            .not_null()
+                            .default("ja"),
+                    )
+                    .col(
+                        ColumnDef::new(GuildSettings::ChartFontFamily)
+                            .string()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(GuildSettings::ChartFontScale)
+                            .double()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(GuildSettings::ChartBackground)
+                            .string()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(GuildSettings::ChartSeriesColors)
+                            .string()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(GuildSettings::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        ColumnDef::new(GuildSettings::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(GuildSettings::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum GuildSettings {
+    Table,
+    ServerId,
+    ChartTheme,
+    ChartLocale,
+    ChartFontFamily,
+    ChartFontScale,
+    ChartBackground,
+    ChartSeriesColors,
+    CreatedAt,
+    UpdatedAt,
+}
@@ -0,0 +1,61 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // SQLiteは1つのALTER TABLEで複数の列を追加できないため、列ごとに追加する
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(GuildSettings::Table)
+                    .add_column(ColumnDef::new(GuildSettings::VoteRequiredRoleId).big_integer())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(GuildSettings::Table)
+                    .add_column(ColumnDef::new(GuildSettings::VoteMinAccountAgeDays).integer())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(GuildSettings::Table)
+                    .add_column(ColumnDef::new(GuildSettings::VoteMinMembershipDays).integer())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for column in [
+            GuildSettings::VoteRequiredRoleId,
+            GuildSettings::VoteMinAccountAgeDays,
+            GuildSettings::VoteMinMembershipDays,
+        ] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(GuildSettings::Table)
+                        .drop_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum GuildSettings {
+    Table,
+    VoteRequiredRoleId,
+    VoteMinAccountAgeDays,
+    VoteMinMembershipDays,
+}
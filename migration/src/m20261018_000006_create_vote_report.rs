@@ -0,0 +1,73 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(VoteReport::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(VoteReport::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(VoteReport::UserId).big_integer().not_null())
+                    .col(ColumnDef::new(VoteReport::GuildId).big_integer().null())
+                    .col(ColumnDef::new(VoteReport::Action).string().null())
+                    .col(ColumnDef::new(VoteReport::Note).string().not_null())
+                    .col(ColumnDef::new(VoteReport::PeriodDate).date().not_null())
+                    .col(
+                        ColumnDef::new(VoteReport::Hidden)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .col(ColumnDef::new(VoteReport::HiddenBy).big_integer().null())
+                    .col(
+                        ColumnDef::new(VoteReport::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_vote_report_period_date")
+                    .table(VoteReport::Table)
+                    .col(VoteReport::PeriodDate)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(VoteReport::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum VoteReport {
+    Table,
+    Id,
+    UserId,
+    GuildId,
+    Action,
+    Note,
+    PeriodDate,
+    Hidden,
+    HiddenBy,
+    CreatedAt,
+}
@@ -0,0 +1,101 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(UserReputation::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(UserReputation::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(UserReputation::GuildId)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(UserReputation::UserId).big_integer().not_null())
+                    .col(
+                        ColumnDef::new(UserReputation::OverrideScore)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(UserReputation::SetBy).big_integer().not_null())
+                    .col(
+                        ColumnDef::new(UserReputation::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_user_reputation_guild_user")
+                    .table(UserReputation::Table)
+                    .col(UserReputation::GuildId)
+                    .col(UserReputation::UserId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(GuildSettings::Table)
+                    .add_column(
+                        ColumnDef::new(GuildSettings::ReputationWeighting)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(GuildSettings::Table)
+                    .drop_column(GuildSettings::ReputationWeighting)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(UserReputation::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum UserReputation {
+    Table,
+    Id,
+    GuildId,
+    UserId,
+    OverrideScore,
+    SetBy,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum GuildSettings {
+    Table,
+    ReputationWeighting,
+}
@@ -0,0 +1,70 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(VoterBan::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(VoterBan::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(VoterBan::GuildId).big_integer().not_null())
+                    .col(ColumnDef::new(VoterBan::UserId).big_integer().not_null())
+                    .col(ColumnDef::new(VoterBan::BannedBy).big_integer().not_null())
+                    .col(ColumnDef::new(VoterBan::Reason).string().null())
+                    .col(
+                        ColumnDef::new(VoterBan::ExpiresAt)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(VoterBan::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_voter_ban_guild_user")
+                    .table(VoterBan::Table)
+                    .col(VoterBan::GuildId)
+                    .col(VoterBan::UserId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(VoterBan::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum VoterBan {
+    Table,
+    Id,
+    GuildId,
+    UserId,
+    BannedBy,
+    Reason,
+    ExpiresAt,
+    CreatedAt,
+}
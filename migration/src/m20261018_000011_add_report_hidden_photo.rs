@@ -0,0 +1,48 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // SQLiteは1つのALTER TABLEで複数の列を追加できないため、列ごとに追加する
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(VoteReport::Table)
+                    .add_column(ColumnDef::new(VoteReport::HiddenPhoto).blob())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(VoteReport::Table)
+                    .add_column(ColumnDef::new(VoteReport::HiddenPhotoFilename).string())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for column in [VoteReport::HiddenPhoto, VoteReport::HiddenPhotoFilename] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(VoteReport::Table)
+                        .drop_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum VoteReport {
+    Table,
+    HiddenPhoto,
+    HiddenPhotoFilename,
+}
@@ -0,0 +1,318 @@
+//! Discordに接続せずにデータベースを直接操作する管理ツール
+
+use chrono::{DateTime, NaiveDate, Utc};
+use clap::{Parser, Subcommand, ValueEnum};
+use kebab_bot::{Error, config::Config, database, logging, services::*};
+use sea_orm::DatabaseConnection;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+#[derive(Parser)]
+#[command(name = "kebab-admin", about = "Kebab Botのデータベース管理ツール")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// 掲示板の一覧を表示する
+    Boards {
+        /// 指定したサーバーの掲示板のみ表示する
+        #[arg(long)]
+        guild: Option<i64>,
+    },
+    /// 現在の期間の投票を表示する
+    Votes {
+        /// 指定したサーバーで投票したユーザーのみ表示する
+        #[arg(long)]
+        guild: Option<i64>,
+    },
+    /// 現在の期間の投票をリセットする
+    ResetVotes {
+        /// 指定したサーバーで投票したユーザーの投票のみリセットする
+        #[arg(long)]
+        guild: Option<i64>,
+    },
+    /// データをCSVまたはJSONで書き出す
+    Export {
+        #[arg(value_enum)]
+        kind: KindArg,
+        #[arg(long, value_enum, default_value = "csv")]
+        format: FormatArg,
+        /// 開始する期間の日付（省略時は現在の期間）
+        #[arg(long)]
+        start: Option<NaiveDate>,
+        /// 終了する期間の日付（省略時は今日）
+        #[arg(long)]
+        end: Option<NaiveDate>,
+        #[arg(long)]
+        guild: Option<i64>,
+        /// 出力先のファイル（省略時はカレントディレクトリに既定の名前で保存）
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+    /// 表計算ソフトで記録していた履歴のCSVを投票履歴に取り込む
+    Import {
+        file: PathBuf,
+        /// 取り込んだ投票を記録するサーバー
+        #[arg(long)]
+        guild: Option<i64>,
+    },
+    /// タイムライングラフをファイルに描画する
+    Chart {
+        /// 出力先のファイル（拡張子が .svg ならSVG、それ以外はPNG）
+        output: PathBuf,
+        /// テーマを適用するサーバー
+        #[arg(long)]
+        guild: Option<i64>,
+        /// 描画する期間の日付（省略時は現在の期間）
+        #[arg(long)]
+        date: Option<NaiveDate>,
+        /// グラフのスタイル（省略時は設定の既定値）
+        #[arg(long, value_enum)]
+        style: Option<StyleArg>,
+        /// 幅（省略時は設定の既定値）
+        #[arg(long)]
+        width: Option<u32>,
+        /// 高さ（省略時は設定の既定値）
+        #[arg(long)]
+        height: Option<u32>,
+        /// PNGの解像度の倍率
+        #[arg(long, default_value_t = 1)]
+        scale: u32,
+    },
+    /// 投票期間の切り替えを試す（`--apply`を付けない限りデータは変更しない）
+    Rollover {
+        /// 判定に使う日時（RFC 3339、省略時は現在時刻）
+        #[arg(long)]
+        at: Option<DateTime<Utc>>,
+        /// 期間が変わっていれば実際に投票をリセットする
+        #[arg(long)]
+        apply: bool,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum KindArg {
+    Votes,
+    Boards,
+    Summaries,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum FormatArg {
+    Csv,
+    Json,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum StyleArg {
+    Line,
+    Bar,
+    StackedArea,
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    dotenvy::dotenv().ok();
+    let cli = Cli::parse();
+
+    match start(cli.command).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("エラー: {}", e);
+            if let Some(hint) = e.hint() {
+                eprintln!("{}", hint);
+            }
+            ExitCode::from(e.exit_code())
+        }
+    }
+}
+
+/// 設定を読み込んでデータベースに接続し、サブコマンドを実行する
+async fn start(command: Command) -> Result<(), Error> {
+    let config = Config::load()?;
+    logging::init(&config.log);
+    VoteService::configure_period(config.period);
+    ChartService::configure(&config.chart);
+
+    let database = database::connect(&config.database.url).await?;
+    run(&database, &config, command).await
+}
+
+async fn run(db: &DatabaseConnection, config: &Config, command: Command) -> Result<(), Error> {
+    match command {
+        Command::Boards { guild } => {
+            let boards = match guild {
+                Some(guild) => BoardService::get_board_data_by_server_id(db, guild).await?,
+                None => BoardService::get_all_board_data(db).await?,
+            };
+            for board in &boards {
+                println!(
+                    "#{} サーバー: {} チャンネル: {} メッセージ: {} グラフ: {} 更新: {}",
+                    board.id,
+                    board.server_id,
+                    board.channel_id,
+                    board.message_id,
+                    board.chart_style,
+                    board.updated_at
+                );
+            }
+            println!("{}件の掲示板", boards.len());
+        }
+        Command::Votes { guild } => {
+            let votes = match guild {
+                Some(guild) => VoteService::get_guild_votes(db, guild).await?,
+                None => VoteService::get_all_votes(db).await?,
+            };
+            for vote in &votes {
+                println!("{} {} {}", vote.updated_at, vote.user_id, vote.action);
+            }
+            for option in &VOTE_OPTIONS {
+                let count = votes
+                    .iter()
+                    .filter(|vote| vote.action == option.action)
+                    .count();
+                println!("{} {}: {}票", option.emoji, option.label, count);
+            }
+        }
+        Command::ResetVotes { guild } => {
+            let result = match guild {
+                Some(guild) => VoteService::delete_guild_votes(db, guild).await?,
+                None => VoteService::delete_all_vote(db).await?,
+            };
+            println!("{}件の投票をリセットしました。", result.rows_affected);
+            println!("掲示板には次回の更新時に反映されます。");
+        }
+        Command::Export {
+            kind,
+            format,
+            start,
+            end,
+            guild,
+            output,
+        } => {
+            let range = match start {
+                Some(start) => ExportRange::Periods {
+                    start,
+                    end: end.unwrap_or_else(VoteService::get_current_jst_afternoon_period),
+                },
+                None => ExportRange::CurrentPeriod,
+            };
+            let request = ExportRequest {
+                kind: match kind {
+                    KindArg::Votes => ExportKind::Votes,
+                    KindArg::Boards => ExportKind::Boards,
+                    KindArg::Summaries => ExportKind::Summaries,
+                },
+                format: match format {
+                    FormatArg::Csv => ExportFormat::Csv,
+                    FormatArg::Json => ExportFormat::Json,
+                },
+                range,
+                guild_id: guild,
+            };
+
+            let file = ExportService::export(db, &request).await?;
+            let path = output.unwrap_or_else(|| PathBuf::from(&file.filename));
+            std::fs::write(&path, file.content)?;
+            println!(
+                "{}件のデータを {} に書き出しました。",
+                file.rows,
+                path.display()
+            );
+        }
+        Command::Import { file, guild } => {
+            let content = std::fs::read_to_string(&file)?;
+            let report = ImportService::import_vote_history_csv(db, &content, guild).await?;
+
+            for error in &report.errors {
+                eprintln!("{}行目: {}", error.line, error.message);
+            }
+            println!(
+                "取り込み: {}件 / 取り込み済みのためスキップ: {}件 / エラー: {}件",
+                report.imported,
+                report.skipped,
+                report.errors.len()
+            );
+        }
+        Command::Chart {
+            output,
+            guild,
+            date,
+            style,
+            width,
+            height,
+            scale,
+        } => {
+            let width = width.unwrap_or(config.chart.width);
+            let height = height.unwrap_or(config.chart.height);
+            let chart_output = if is_svg(&output) {
+                ChartOutput::svg(width, height)
+            } else {
+                ChartOutput::png(width, height, scale)
+            };
+
+            let current_period = VoteService::get_current_jst_afternoon_period();
+            let votes = match date {
+                Some(date) if date != current_period => {
+                    VoteService::get_period_final_votes(db, date).await?
+                }
+                _ => VoteService::get_all_votes(db).await?,
+            };
+            let theme = GuildSettingsService::get_chart_theme(db, guild).await?;
+            let style = match style {
+                Some(StyleArg::Line) => ChartStyle::Line,
+                Some(StyleArg::Bar) => ChartStyle::Bar,
+                Some(StyleArg::StackedArea) => ChartStyle::StackedArea,
+                None => config.chart.style,
+            };
+
+            let chart =
+                ChartService::generate_vote_timeline_chart(votes, style, &theme, &chart_output)
+                    .await?;
+            std::fs::write(&output, chart)?;
+            println!("グラフを {} に書き出しました。", output.display());
+        }
+        Command::Rollover { at, apply } => {
+            let at = at.unwrap_or_else(Utc::now);
+            let period = VoteService::get_jst_afternoon_period(at);
+            let latest_period = VoteService::get_latest_vote_jst_afternoon_period(db).await?;
+            let votes = VoteService::get_all_votes(db).await?;
+
+            println!("判定日時: {} → 投票期間: {}", at, period);
+            match latest_period {
+                Some(latest_period) if latest_period < period => {
+                    println!(
+                        "投票期間が変わっています: {} → {}（{}件の投票がリセット対象）",
+                        latest_period,
+                        period,
+                        votes.len()
+                    );
+                    if apply {
+                        let result = VoteService::delete_all_vote(db).await?;
+                        println!("{}件の投票をリセットしました。", result.rows_affected);
+                    } else {
+                        println!("--apply を付けると実際にリセットします。");
+                    }
+                }
+                Some(latest_period) => {
+                    println!(
+                        "最新の投票は {} の期間のため、リセットされません。",
+                        latest_period
+                    );
+                }
+                None => println!("投票データがありません。"),
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// 出力先の拡張子がSVGかどうか
+fn is_svg(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("svg"))
+}
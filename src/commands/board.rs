@@ -1,12 +1,18 @@
 use crate::{Context, Error, services::*};
 use poise::CreateReply;
+use tracing::error;
 
 /// 板を出すコマンド
 #[poise::command(slash_command)]
 pub async fn create_board(ctx: Context<'_>) -> Result<(), Error> {
+    let server_id = ctx.guild_id().ok_or(Error::NotInGuild)?.get() as i64;
+    // 既に掲示板があれば、監査ログでは移動として記録する
+    let previous = BoardService::get_board_data_by_server_id(&ctx.data().database, server_id)
+        .await?
+        .into_iter()
+        .next();
     let res = ctx.say("板").await?;
 
-    let server_id = ctx.guild_id().unwrap().get() as i64;
     let channel_id = ctx.channel_id().get() as i64;
     let message_id = res.message().await?.id.get() as i64;
 
@@ -18,6 +24,22 @@ pub async fn create_board(ctx: Context<'_>) -> Result<(), Error> {
     )
     .await?;
 
+    let user_id = ctx.author().id.get() as i64;
+    let event = match previous {
+        Some(previous) => AuditEvent::BoardMoved {
+            user_id,
+            old_channel_id: previous.channel_id,
+            channel_id,
+            message_id,
+        },
+        None => AuditEvent::BoardCreated {
+            user_id,
+            channel_id,
+            message_id,
+        },
+    };
+    ctx.data().audit_log.record(server_id, event);
+
     let rep = ctx
         .reply_builder(CreateReply::default())
         .content(format!(
@@ -35,22 +57,11 @@ pub async fn update_board(ctx: Context<'_>) -> Result<(), Error> {
     ctx.defer_ephemeral().await?;
 
     // 日付チェックを行い、必要に応じて投票をリセットして掲示板を更新
-    if let Err(e) = VoteService::check_reset_and_update_board_if_new_day(
-        &ctx.data().database,
-        ctx.serenity_context(),
-    )
-    .await
+    if let Err(e) =
+        VoteService::check_reset_and_update_board_if_new_day(ctx.data(), ctx.serenity_context())
+            .await
     {
-        eprintln!("日付チェック中にエラーが発生しました: {}", e);
-    }
-
-    // 投票データを取得してタイムラインチャートを生成
-    let votes = VoteService::get_all_votes(&ctx.data().database).await?;
-    if !votes.is_empty() {
-        let timeline_path = "vote_timeline.png";
-        if let Err(e) = ChartService::generate_vote_timeline_chart(votes, timeline_path).await {
-            eprintln!("タイムラインチャート生成エラー: {}", e);
-        }
+        error!(error = %e, "日付チェック中にエラーが発生しました");
     }
 
     let board_data = BoardService::get_all_board_data(&ctx.data().database).await?;
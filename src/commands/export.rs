@@ -0,0 +1,212 @@
+use crate::{Context, Error, services::*};
+use chrono::NaiveDate;
+use poise::{
+    CreateReply,
+    serenity_prelude::{Attachment, CreateAttachment},
+};
+use tracing::error;
+
+/// エクスポートするデータ
+#[derive(Debug, Clone, Copy, poise::ChoiceParameter)]
+pub enum ExportKindChoice {
+    #[name = "投票履歴"]
+    Votes,
+    #[name = "掲示板"]
+    Boards,
+    #[name = "期間ごとの集計"]
+    Summaries,
+}
+
+impl ExportKindChoice {
+    fn kind(&self) -> ExportKind {
+        match self {
+            ExportKindChoice::Votes => ExportKind::Votes,
+            ExportKindChoice::Boards => ExportKind::Boards,
+            ExportKindChoice::Summaries => ExportKind::Summaries,
+        }
+    }
+}
+
+/// エクスポートの出力形式
+#[derive(Debug, Clone, Copy, poise::ChoiceParameter)]
+pub enum ExportFormatChoice {
+    #[name = "CSV"]
+    Csv,
+    #[name = "JSON"]
+    Json,
+}
+
+impl ExportFormatChoice {
+    fn format(&self) -> ExportFormat {
+        match self {
+            ExportFormatChoice::Csv => ExportFormat::Csv,
+            ExportFormatChoice::Json => ExportFormat::Json,
+        }
+    }
+}
+
+/// 一度にエクスポートできる期間の上限（日数）
+const MAX_EXPORT_DAYS: i64 = 366;
+
+/// データをCSVまたはJSONでエクスポートするコマンド
+// 開始日を省略すると現在の期間のデータを出力する
+#[poise::command(slash_command, guild_only, default_member_permissions = "MANAGE_GUILD")]
+pub async fn export(
+    ctx: Context<'_>,
+    #[description = "エクスポートするデータ"] data: ExportKindChoice,
+    #[description = "出力形式（省略時はCSV）"] format: Option<ExportFormatChoice>,
+    #[description = "開始する期間の日付 (YYYY-MM-DD、省略時は現在の期間)"] start_date: Option<
+        String,
+    >,
+    #[description = "終了する期間の日付 (YYYY-MM-DD、省略時は今日)"] end_date: Option<String>,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or(Error::NotInGuild)?;
+    ctx.defer_ephemeral().await?;
+
+    let range = match parse_range(start_date.as_deref(), end_date.as_deref()) {
+        Ok(range) => range,
+        Err(message) => {
+            let rep = ctx
+                .reply_builder(CreateReply::default())
+                .content(format!("❌ {}", message))
+                .ephemeral(true);
+            ctx.send(rep).await?;
+            return Ok(());
+        }
+    };
+
+    let request = ExportRequest {
+        kind: data.kind(),
+        format: format.unwrap_or(ExportFormatChoice::Csv).format(),
+        range,
+        guild_id: Some(guild_id.get() as i64),
+    };
+
+    match ExportService::export(&ctx.data().database, &request).await {
+        Ok(file) => {
+            let rep = ctx
+                .reply_builder(CreateReply::default())
+                .content(format!(
+                    "📦 {}件のデータをエクスポートしました。",
+                    file.rows
+                ))
+                .attachment(CreateAttachment::bytes(file.content, file.filename))
+                .ephemeral(true);
+            ctx.send(rep).await?;
+        }
+        Err(e) => {
+            error!(error = %e, "エクスポート中にエラーが発生しました");
+            let rep = ctx
+                .reply_builder(CreateReply::default())
+                .content("❌ エクスポートに失敗しました。")
+                .ephemeral(true);
+            ctx.send(rep).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// 開始日と終了日からエクスポートする期間の範囲を作成する
+fn parse_range(start_date: Option<&str>, end_date: Option<&str>) -> Result<ExportRange, String> {
+    let Some(start_date) = start_date else {
+        return Ok(ExportRange::CurrentPeriod);
+    };
+
+    let start = NaiveDate::parse_from_str(start_date, "%Y-%m-%d")
+        .map_err(|_| "開始日は YYYY-MM-DD 形式で指定してください。".to_string())?;
+    let end = match end_date {
+        Some(end_date) => NaiveDate::parse_from_str(end_date, "%Y-%m-%d")
+            .map_err(|_| "終了日は YYYY-MM-DD 形式で指定してください。".to_string())?,
+        None => VoteService::get_current_jst_afternoon_period(),
+    };
+
+    if start > end {
+        return Err("開始日は終了日以前の日付を指定してください。".to_string());
+    }
+    if (end - start).num_days() >= MAX_EXPORT_DAYS {
+        return Err(format!(
+            "エクスポートできる期間は最大{}日です。",
+            MAX_EXPORT_DAYS
+        ));
+    }
+
+    Ok(ExportRange::Periods { start, end })
+}
+
+/// 取り込み結果に表示するエラー行の上限
+const MAX_REPORTED_IMPORT_ERRORS: usize = 10;
+/// 取り込めるCSVファイルの最大サイズ（バイト）
+const MAX_IMPORT_FILE_SIZE: u32 = 1024 * 1024;
+
+/// 表計算ソフトで記録していた履歴のCSVを投票履歴に取り込むコマンド
+// 列: date, time（日本時間）または voted_at, action, user_id（任意）。/exportの履歴も取り込め、同じファイルを再度取り込んでも重複しない
+#[poise::command(slash_command, guild_only, default_member_permissions = "MANAGE_GUILD")]
+pub async fn import_history(
+    ctx: Context<'_>,
+    #[description = "取り込むCSVファイル（date, time, action, user_id の列、または/exportの履歴）"]
+    file: Attachment,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or(Error::NotInGuild)?;
+    ctx.defer_ephemeral().await?;
+
+    if file.size > MAX_IMPORT_FILE_SIZE {
+        let rep = ctx
+            .reply_builder(CreateReply::default())
+            .content("❌ CSVファイルは1MB以下にしてください。")
+            .ephemeral(true);
+        ctx.send(rep).await?;
+        return Ok(());
+    }
+
+    let content = String::from_utf8(file.download().await?);
+    let result = match content {
+        Ok(content) => {
+            ImportService::import_vote_history_csv(
+                &ctx.data().database,
+                &content,
+                Some(guild_id.get() as i64),
+            )
+            .await
+        }
+        Err(_) => Err(Error::InvalidInput(
+            "CSVファイルはUTF-8で保存してください。".to_string(),
+        )),
+    };
+
+    let message = match result {
+        Ok(report) => {
+            // 取り込んだ過去の期間の履歴を信頼度に反映する
+            ctx.data()
+                .reputation_cache
+                .invalidate_guild(guild_id.get() as i64);
+            let mut message = format!(
+                "📥 取り込み: {}件 / 取り込み済みのためスキップ: {}件 / エラー: {}件",
+                report.imported,
+                report.skipped,
+                report.errors.len()
+            );
+            for error in report.errors.iter().take(MAX_REPORTED_IMPORT_ERRORS) {
+                message.push_str(&format!("\n・{}行目: {}", error.line, error.message));
+            }
+            if report.errors.len() > MAX_REPORTED_IMPORT_ERRORS {
+                message.push_str(&format!(
+                    "\n・ほか{}件",
+                    report.errors.len() - MAX_REPORTED_IMPORT_ERRORS
+                ));
+            }
+            message
+        }
+        Err(e) => {
+            error!(error = %e, "投票履歴の取り込みに失敗しました");
+            e.user_message()
+        }
+    };
+
+    let rep = ctx
+        .reply_builder(CreateReply::default())
+        .content(message)
+        .ephemeral(true);
+    ctx.send(rep).await?;
+    Ok(())
+}
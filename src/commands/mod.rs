@@ -1,10 +1,28 @@
 pub mod basic;
 pub mod board;
+pub mod export;
+pub mod moderation;
+pub mod report;
+pub mod reputation;
+pub mod settings;
+pub mod stats;
 pub mod vote;
 
 // 基本コマンドの再エクスポート
 pub use basic::{help, ping};
 // 掲示板コマンドの再エクスポート
 pub use board::{create_board, update_board};
+// エクスポート・インポートコマンドの再エクスポート
+pub use export::{export, import_history};
+// モデレーター用コマンドの再エクスポート
+pub use moderation::moderation;
+// 報告コマンドの再エクスポート
+pub use report::report;
+// 信頼度コマンドの再エクスポート
+pub use reputation::reputation;
+// 設定コマンドの再エクスポート
+pub use settings::{audit_log_channel, board_chart_style, chart_theme, vote_eligibility};
+// 統計コマンドの再エクスポート
+pub use stats::{leaderboard, stats};
 // 投票コマンドの再エクスポート
-pub use vote::{reset_votes, vote_chart, vote_results};
+pub use vote::{reset_votes, vote, vote_chart, vote_results};
@@ -0,0 +1,195 @@
+use crate::{Context, Error, services::*};
+use chrono::{TimeDelta, Utc};
+use poise::serenity_prelude as serenity;
+use poise::{ChoiceParameter, CreateReply};
+
+/// 投票を禁止する期間
+#[derive(Debug, Clone, Copy, poise::ChoiceParameter)]
+pub enum BanDurationChoice {
+    #[name = "1時間"]
+    OneHour,
+    #[name = "1日"]
+    OneDay,
+    #[name = "1週間"]
+    OneWeek,
+    #[name = "30日"]
+    ThirtyDays,
+    #[name = "無期限"]
+    Permanent,
+}
+
+impl BanDurationChoice {
+    /// 禁止する長さ（無期限なら`None`）
+    pub fn duration(&self) -> Option<TimeDelta> {
+        match self {
+            BanDurationChoice::OneHour => Some(TimeDelta::hours(1)),
+            BanDurationChoice::OneDay => Some(TimeDelta::days(1)),
+            BanDurationChoice::OneWeek => Some(TimeDelta::weeks(1)),
+            BanDurationChoice::ThirtyDays => Some(TimeDelta::days(30)),
+            BanDurationChoice::Permanent => None,
+        }
+    }
+}
+
+/// 投票を管理するモデレーター用のコマンド
+// `mod`は予約語のため、関数名を変えてコマンド名を指定する
+// `default_member_permissions`はサーバーの管理者が変更できるため、各サブコマンドでも権限を確認する
+#[poise::command(
+    slash_command,
+    rename = "mod",
+    guild_only,
+    default_member_permissions = "MODERATE_MEMBERS",
+    subcommands("remove_vote", "ban_voter", "unban_voter"),
+    subcommand_required
+)]
+pub async fn moderation(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// ユーザーの現在の投票を削除する
+#[poise::command(slash_command, guild_only, required_permissions = "MODERATE_MEMBERS")]
+pub async fn remove_vote(
+    ctx: Context<'_>,
+    #[description = "投票を削除するユーザー"] user: serenity::User,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or(Error::NotInGuild)?;
+    ctx.defer_ephemeral().await?;
+
+    let removed = ModerationService::remove_vote(
+        ctx.data(),
+        guild_id.get() as i64,
+        user.id.get() as i64,
+        ctx.author().id.get() as i64,
+    )
+    .await?;
+
+    let content = match removed {
+        Some(old_action) => {
+            BoardUIService::refresh_all_board_messages_serenity(ctx.serenity_context(), ctx.data())
+                .await?;
+            format!(
+                "✅ <@{}>の投票（{}）を削除しました。",
+                user.id,
+                VoteService::action_label(&old_action)
+            )
+        }
+        None => format!(
+            "<@{}>は現在の期間にこのサーバーで投票していません。",
+            user.id
+        ),
+    };
+    let rep = ctx
+        .reply_builder(CreateReply::default())
+        .content(content)
+        .ephemeral(true);
+    ctx.send(rep).await?;
+    Ok(())
+}
+
+/// このサーバーの掲示板からの投票を禁止し、集計からも外す
+// 投票はサーバーをまたいで1つなので、他のサーバーの掲示板からの投票はそのサーバーの集計に残る
+#[poise::command(slash_command, guild_only, required_permissions = "MODERATE_MEMBERS")]
+pub async fn ban_voter(
+    ctx: Context<'_>,
+    #[description = "投票を禁止するユーザー"] user: serenity::User,
+    #[description = "禁止する期間（省略時は無期限）"] duration: Option<BanDurationChoice>,
+    #[description = "禁止する理由（監査用）"]
+    #[max_length = 200]
+    reason: Option<String>,
+    #[description = "現在の投票も削除する（省略時は削除。禁止中は集計に含めない）"]
+    remove_vote: Option<bool>,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or(Error::NotInGuild)?;
+    let server_id = guild_id.get() as i64;
+    let target_user_id = user.id.get() as i64;
+    let moderator_id = ctx.author().id.get() as i64;
+    ctx.defer_ephemeral().await?;
+
+    let duration = duration.unwrap_or(BanDurationChoice::Permanent);
+    let expires_at = duration.duration().map(|duration| Utc::now() + duration);
+    ModerationService::ban_voter(
+        &ctx.data().database,
+        server_id,
+        target_user_id,
+        moderator_id,
+        reason.clone(),
+        expires_at,
+    )
+    .await?;
+    ctx.data().audit_log.record(
+        server_id,
+        AuditEvent::VoterBanned {
+            user_id: moderator_id,
+            target_user_id,
+            expires_at,
+            reason,
+        },
+    );
+
+    let mut content = match expires_at {
+        Some(expires_at) => format!(
+            "✅ <@{}>の投票を{}禁止しました（<t:{}:f>まで）。",
+            user.id,
+            duration.name(),
+            expires_at.timestamp()
+        ),
+        None => format!("✅ <@{}>の投票を無期限に禁止しました。", user.id),
+    };
+    if remove_vote.unwrap_or(true) {
+        let removed =
+            ModerationService::remove_vote(ctx.data(), server_id, target_user_id, moderator_id)
+                .await?;
+        if let Some(old_action) = removed {
+            content.push_str(&format!(
+                "\n現在の投票（{}）も削除しました。",
+                VoteService::action_label(&old_action)
+            ));
+        }
+    }
+    // 投票を削除しなくても、禁止したユーザーは掲示板の集計から外れる
+    BoardUIService::refresh_all_board_messages_serenity(ctx.serenity_context(), ctx.data()).await?;
+
+    let rep = ctx
+        .reply_builder(CreateReply::default())
+        .content(content)
+        .ephemeral(true);
+    ctx.send(rep).await?;
+    Ok(())
+}
+
+/// ユーザーの投票の禁止を解除する
+#[poise::command(slash_command, guild_only, required_permissions = "MODERATE_MEMBERS")]
+pub async fn unban_voter(
+    ctx: Context<'_>,
+    #[description = "禁止を解除するユーザー"] user: serenity::User,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or(Error::NotInGuild)?;
+    let server_id = guild_id.get() as i64;
+    let target_user_id = user.id.get() as i64;
+    ctx.defer_ephemeral().await?;
+
+    let unbanned =
+        ModerationService::unban_voter(&ctx.data().database, server_id, target_user_id).await?;
+    let content = if unbanned {
+        ctx.data().audit_log.record(
+            server_id,
+            AuditEvent::VoterUnbanned {
+                user_id: ctx.author().id.get() as i64,
+                target_user_id,
+            },
+        );
+        // 禁止中に集計から外していた投票を掲示板に戻す
+        BoardUIService::refresh_all_board_messages_serenity(ctx.serenity_context(), ctx.data())
+            .await?;
+        format!("✅ <@{}>の投票の禁止を解除しました。", user.id)
+    } else {
+        format!("<@{}>は投票を禁止されていません。", user.id)
+    };
+
+    let rep = ctx
+        .reply_builder(CreateReply::default())
+        .content(content)
+        .ephemeral(true);
+    ctx.send(rep).await?;
+    Ok(())
+}
@@ -0,0 +1,381 @@
+use crate::entities::vote_report::Model as ReportModel;
+use crate::metrics::METRICS;
+use crate::{Context, Error, services::*};
+use chrono::Utc;
+use poise::{
+    CreateReply,
+    serenity_prelude::{
+        Attachment, ChannelId, Colour, CreateAllowedMentions, CreateAttachment, CreateEmbed,
+        CreateEmbedFooter, CreateMessage,
+    },
+};
+use tracing::{error, info, warn};
+
+/// `/report list`で表示する報告の件数
+const LIST_REPORT_LIMIT: u64 = 10;
+/// 報告の写真として受け付けるファイルの種類
+const PHOTO_CONTENT_TYPES: [&str; 4] = ["image/png", "image/jpeg", "image/gif", "image/webp"];
+
+/// ケバブ屋の様子を報告・管理するコマンド
+// 写真の報告は誰でも使えるため、管理用のサブコマンドにだけ権限を設定する
+#[poise::command(
+    slash_command,
+    guild_only,
+    subcommands("photo", "list", "hide", "unhide"),
+    subcommand_required
+)]
+pub async fn report(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// ケバブ屋の写真を現在の期間の自分の投票と一緒に報告する
+#[poise::command(slash_command, guild_only)]
+pub async fn photo(
+    ctx: Context<'_>,
+    #[description = "ケバブ屋の写真"] image: Attachment,
+    #[description = "写真の説明（任意）"]
+    #[max_length = 200]
+    comment: Option<String>,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or(Error::NotInGuild)?;
+    let server_id = guild_id.get() as i64;
+    let user_id = ctx.author().id.get() as i64;
+    ctx.defer_ephemeral().await?;
+
+    check_photo(&image, ctx.data().config.image.max_size_bytes).map_err(Error::InvalidInput)?;
+
+    // 投票と同じ条件を満たすユーザーだけが報告できる
+    let ban =
+        ModerationService::get_active_ban(&ctx.data().database, server_id, user_id, Utc::now())
+            .await?;
+    if let Some(ban) = ban {
+        METRICS.record_ineligible_vote("banned");
+        let rep = ctx
+            .reply_builder(CreateReply::default())
+            .content(ModerationService::ban_message(&ban))
+            .ephemeral(true);
+        ctx.send(rep).await?;
+        return Ok(());
+    }
+    if let Some(member) = ctx.author_member().await {
+        let eligibility =
+            GuildSettingsService::get_vote_eligibility(&ctx.data().database, Some(server_id))
+                .await?;
+        if let Err(reason) = eligibility.check(&Voter::from_member(&member), Utc::now()) {
+            METRICS.record_ineligible_vote(reason.code());
+            let rep = ctx
+                .reply_builder(CreateReply::default())
+                .content(reason.message())
+                .ephemeral(true);
+            ctx.send(rep).await?;
+            return Ok(());
+        }
+    }
+
+    // 前の期間の投票に写真を紐付けないよう、先に日付チェックを行う
+    if let Err(e) =
+        VoteService::check_reset_and_update_board_if_new_day(ctx.data(), ctx.serenity_context())
+            .await
+    {
+        error!(error = %e, "日付チェック中にエラーが発生しました");
+    }
+    if VoteService::get_vote(&ctx.data().database, user_id)
+        .await?
+        .is_none()
+    {
+        return Err(Error::InvalidInput(
+            "写真を報告するには、先に掲示板のボタンで投票してください。".to_string(),
+        ));
+    }
+
+    let cooldown_key = VoteCooldownKey {
+        guild_id: Some(server_id),
+        user_id,
+    };
+    if let Err(remaining) = ctx.data().report_cooldown.try_acquire(cooldown_key) {
+        METRICS.record_rate_limited_vote("report");
+        let rep = ctx
+            .reply_builder(CreateReply::default())
+            .content(format!(
+                "⏳ 報告は{}秒後にできます。",
+                remaining.as_secs_f64().ceil() as u64
+            ))
+            .ephemeral(true);
+        ctx.send(rep).await?;
+        return Ok(());
+    }
+
+    let note = comment
+        .as_deref()
+        .and_then(ReportService::normalize_note)
+        .unwrap_or_default();
+    let reposted = match image.download().await {
+        Ok(photo) => {
+            let channel_id = photo_channel(ctx, server_id).await?;
+            repost_photo(
+                ctx,
+                channel_id,
+                user_id,
+                photo,
+                image.filename.clone(),
+                &note,
+            )
+            .await
+        }
+        Err(e) => Err(e.into()),
+    };
+    let report = match reposted {
+        Ok(photo) => ReportService::create_report(
+            &ctx.data().database,
+            user_id,
+            Some(server_id),
+            note,
+            Some(photo),
+        )
+        .await
+        .map_err(Error::from),
+        Err(e) => Err(e),
+    };
+    let report = match report {
+        Ok(report) => report,
+        Err(e) => {
+            // 保存できなかった報告では待ち時間を発生させない
+            ctx.data().report_cooldown.release(cooldown_key);
+            return Err(e);
+        }
+    };
+    info!(report_id = report.id, "写真の報告を保存しました");
+
+    BoardUIService::refresh_all_board_messages_serenity(ctx.serenity_context(), ctx.data()).await?;
+
+    let rep = ctx
+        .reply_builder(CreateReply::default())
+        .content("📷 写真を受け付けました。掲示板のサムネイルに表示されます。")
+        .ephemeral(true);
+    ctx.send(rep).await?;
+    Ok(())
+}
+
+/// 報告の写真として受け付けられる画像か確認する
+/// ダウンロードする前に、Discordが伝えるファイルの種類とサイズで判定する
+fn check_photo(image: &Attachment, max_size_bytes: u32) -> Result<(), String> {
+    let is_image = image
+        .content_type
+        .as_deref()
+        .is_some_and(|content_type| PHOTO_CONTENT_TYPES.contains(&content_type));
+    if !is_image {
+        return Err("PNG・JPEG・GIF・WebPの画像ファイルを添付してください。".to_string());
+    }
+    if image.size > max_size_bytes {
+        return Err(format!(
+            "写真は{}MB以下にしてください。",
+            max_size_bytes as f64 / (1024.0 * 1024.0)
+        ));
+    }
+    Ok(())
+}
+
+/// 写真を投稿し直すチャンネル（掲示板のチャンネル、掲示板がなければコマンドを使ったチャンネル）
+async fn photo_channel(ctx: Context<'_>, server_id: i64) -> Result<ChannelId, Error> {
+    Ok(
+        BoardService::get_board_data_by_server_id(&ctx.data().database, server_id)
+            .await?
+            .first()
+            .map(|board| ChannelId::new(board.channel_id as u64))
+            .unwrap_or(ctx.channel_id()),
+    )
+}
+
+/// 報告した`user_id`のユーザーの写真をチャンネルに投稿し直す
+/// コマンドに添付されたファイルのURLは期限が切れると取得し直せないため、
+/// メッセージとして残しておき、掲示板を更新する時に新しいURLを取得できるようにする
+async fn repost_photo(
+    ctx: Context<'_>,
+    channel_id: ChannelId,
+    user_id: i64,
+    photo: Vec<u8>,
+    filename: String,
+    note: &str,
+) -> Result<ReportPhoto, Error> {
+    let content = if note.is_empty() {
+        format!("📷 <@{}>さんの写真の報告", user_id)
+    } else {
+        format!("📷 <@{}>さんの写真の報告: {}", user_id, note)
+    };
+    // 報告したユーザーへの通知は送らない
+    let message = CreateMessage::new()
+        .content(content)
+        .add_file(CreateAttachment::bytes(photo, filename))
+        .allowed_mentions(CreateAllowedMentions::new());
+    let message = channel_id.send_message(ctx, message).await?;
+    let attachment = message
+        .attachments
+        .first()
+        .ok_or_else(|| Error::InvalidInput("写真を投稿できませんでした。".to_string()))?;
+
+    Ok(ReportPhoto {
+        url: attachment.url.clone(),
+        channel_id: channel_id.get() as i64,
+        message_id: message.id.get() as i64,
+    })
+}
+
+/// 現在の期間の報告をIDと一緒に表示する
+#[poise::command(slash_command, guild_only, required_permissions = "MANAGE_MESSAGES")]
+pub async fn list(ctx: Context<'_>) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or(Error::NotInGuild)?;
+    let reports = ReportService::get_period_reports(
+        &ctx.data().database,
+        guild_id.get() as i64,
+        VoteService::get_current_jst_afternoon_period(),
+        LIST_REPORT_LIMIT,
+    )
+    .await?;
+
+    let description = if reports.is_empty() {
+        "現在の期間の報告はありません。".to_string()
+    } else {
+        reports
+            .iter()
+            .map(|report| {
+                format!(
+                    "`#{}` {}{}",
+                    report.id,
+                    ReportService::format_report(report),
+                    if report.hidden { "（非表示）" } else { "" }
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    let embed = CreateEmbed::new()
+        .title("📝 現在の期間の報告")
+        .description(description)
+        .footer(CreateEmbedFooter::new(format!(
+            "新しい順に最大{}件",
+            LIST_REPORT_LIMIT
+        )))
+        .colour(Colour::from_rgb(0, 255, 0));
+    let rep = ctx
+        .reply_builder(CreateReply::default())
+        .embed(embed)
+        .ephemeral(true);
+    ctx.send(rep).await?;
+    Ok(())
+}
+
+/// 報告を掲示板に表示しないようにする
+#[poise::command(slash_command, guild_only, required_permissions = "MANAGE_MESSAGES")]
+pub async fn hide(
+    ctx: Context<'_>,
+    #[description = "報告のID（/report list で確認できます）"] id: i32,
+) -> Result<(), Error> {
+    set_report_hidden(ctx, id, true).await
+}
+
+/// 非表示にした報告を掲示板に表示し直す
+#[poise::command(slash_command, guild_only, required_permissions = "MANAGE_MESSAGES")]
+pub async fn unhide(
+    ctx: Context<'_>,
+    #[description = "報告のID（/report list で確認できます）"] id: i32,
+) -> Result<(), Error> {
+    set_report_hidden(ctx, id, false).await
+}
+
+async fn set_report_hidden(ctx: Context<'_>, id: i32, hidden: bool) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or(Error::NotInGuild)?;
+    ctx.defer_ephemeral().await?;
+
+    let moderator_id = ctx.author().id.get() as i64;
+    let updated = ReportService::set_hidden(
+        &ctx.data().database,
+        guild_id.get() as i64,
+        id,
+        hidden,
+        moderator_id,
+    )
+    .await?;
+    let Some(report) = updated else {
+        return Err(Error::InvalidInput(format!(
+            "報告#{}が見つかりません。",
+            id
+        )));
+    };
+
+    // 隠した報告の写真はチャンネルにも残さず、表示に戻したら投稿し直す
+    let photo_result = if hidden {
+        ReportService::withdraw_photo(ctx.http(), &ctx.data().database, report)
+            .await
+            .map(|_| ())
+    } else {
+        restore_photo(ctx, report).await
+    };
+    let photo_warning = match photo_result {
+        Ok(()) => String::new(),
+        Err(e) => {
+            warn!(report_id = id, error = %e, "報告の写真を更新できませんでした");
+            format!(
+                "\n⚠️ 写真のメッセージを{}できませんでした。\n{}",
+                if hidden {
+                    "削除"
+                } else {
+                    "投稿し直すことが"
+                },
+                e.user_message()
+            )
+        }
+    };
+
+    ctx.data().audit_log.record(
+        guild_id.get() as i64,
+        AuditEvent::ReportHidden {
+            user_id: moderator_id,
+            report_id: id,
+            hidden,
+        },
+    );
+    BoardUIService::refresh_all_board_messages_serenity(ctx.serenity_context(), ctx.data()).await?;
+
+    let rep = ctx
+        .reply_builder(CreateReply::default())
+        .content(format!(
+            "✅ 報告#{}を{}ました。{}",
+            id,
+            if hidden {
+                "非表示にし"
+            } else {
+                "表示に戻し"
+            },
+            photo_warning
+        ))
+        .ephemeral(true);
+    ctx.send(rep).await?;
+    Ok(())
+}
+
+/// 非表示にした時に取り下げた写真を投稿し直す
+async fn restore_photo(ctx: Context<'_>, report: ReportModel) -> Result<(), Error> {
+    let (Some(photo), Some(filename)) = (
+        report.hidden_photo.clone(),
+        report.hidden_photo_filename.clone(),
+    ) else {
+        return Ok(());
+    };
+
+    let channel_id = match report.photo_channel_id {
+        Some(channel_id) => ChannelId::new(channel_id as u64),
+        None => photo_channel(ctx, report.guild_id.unwrap_or_default()).await?,
+    };
+    let photo = repost_photo(
+        ctx,
+        channel_id,
+        report.user_id,
+        photo,
+        filename,
+        &report.note,
+    )
+    .await?;
+    ReportService::restore_photo(&ctx.data().database, report, photo).await?;
+    Ok(())
+}
@@ -0,0 +1,176 @@
+use crate::{Context, Error, services::*};
+use poise::{
+    CreateReply,
+    serenity_prelude::{self as serenity, Colour, CreateEmbed},
+};
+
+/// 投票者の信頼度を確認・設定するコマンド
+#[poise::command(
+    slash_command,
+    guild_only,
+    default_member_permissions = "MANAGE_GUILD",
+    subcommands("show", "set", "clear", "weighting"),
+    subcommand_required
+)]
+pub async fn reputation(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// ユーザーの信頼度と票の重みを表示する
+#[poise::command(slash_command, guild_only)]
+pub async fn show(
+    ctx: Context<'_>,
+    #[description = "確認するユーザー"] user: serenity::User,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or(Error::NotInGuild)?;
+    let server_id = guild_id.get() as i64;
+    let database = &ctx.data().database;
+
+    let reputation = ReputationService::get_user_reputation(
+        database,
+        &ctx.data().reputation_cache,
+        server_id,
+        user.id.get() as i64,
+    )
+    .await?;
+    let weighting = GuildSettingsService::get_reputation_weighting(database, server_id).await?;
+
+    let embed = CreateEmbed::new()
+        .title(format!("⚖️ {}の信頼度", user.name))
+        .field(
+            "計算値",
+            format!(
+                "{:.0}（多数派が決まった{}期間から）",
+                reputation.computed, reputation.judged_periods
+            ),
+            true,
+        )
+        .field(
+            "管理者の設定",
+            reputation
+                .override_score
+                .map_or("なし".to_string(), |score| score.to_string()),
+            true,
+        )
+        .field(
+            "票の重み",
+            format!(
+                "{:.2}票{}",
+                reputation.weight(),
+                if weighting {
+                    ""
+                } else {
+                    "（このサーバーでは重み付けは無効です）"
+                }
+            ),
+            false,
+        )
+        .colour(Colour::from_rgb(52, 152, 219));
+    let rep = ctx
+        .reply_builder(CreateReply::default())
+        .embed(embed)
+        .ephemeral(true);
+    ctx.send(rep).await?;
+    Ok(())
+}
+
+/// ユーザーの信頼度を設定して計算値を上書きする
+#[poise::command(slash_command, guild_only)]
+pub async fn set(
+    ctx: Context<'_>,
+    #[description = "設定するユーザー"] user: serenity::User,
+    #[description = "信頼度（0〜100、50で1票）"]
+    #[max = 100]
+    score: u32,
+) -> Result<(), Error> {
+    override_reputation(ctx, user, Some(score)).await
+}
+
+/// ユーザーの信頼度の設定を外して計算値に戻す
+#[poise::command(slash_command, guild_only)]
+pub async fn clear(
+    ctx: Context<'_>,
+    #[description = "設定を外すユーザー"] user: serenity::User,
+) -> Result<(), Error> {
+    override_reputation(ctx, user, None).await
+}
+
+/// 掲示板と投票結果で信頼度による票の重み付けを行うかを設定する
+#[poise::command(slash_command, guild_only)]
+pub async fn weighting(
+    ctx: Context<'_>,
+    #[description = "重み付けした票数を表示する"] enabled: bool,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or(Error::NotInGuild)?;
+    let server_id = guild_id.get() as i64;
+    ctx.defer_ephemeral().await?;
+
+    GuildSettingsService::update_reputation_weighting(&ctx.data().database, server_id, enabled)
+        .await?;
+    ctx.data().audit_log.record(
+        server_id,
+        AuditEvent::ReputationWeightingChanged {
+            user_id: ctx.author().id.get() as i64,
+            enabled,
+        },
+    );
+    BoardUIService::refresh_all_board_messages_serenity(ctx.serenity_context(), ctx.data()).await?;
+
+    let rep = ctx
+        .reply_builder(CreateReply::default())
+        .content(if enabled {
+            "✅ 信頼度で重み付けした票数を掲示板と投票結果に表示します。"
+        } else {
+            "✅ 信頼度による票の重み付けを止めました。"
+        })
+        .ephemeral(true);
+    ctx.send(rep).await?;
+    Ok(())
+}
+
+async fn override_reputation(
+    ctx: Context<'_>,
+    user: serenity::User,
+    score: Option<u32>,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or(Error::NotInGuild)?;
+    let server_id = guild_id.get() as i64;
+    let user_id = ctx.author().id.get() as i64;
+    ctx.defer_ephemeral().await?;
+
+    ReputationService::set_override(
+        &ctx.data().database,
+        server_id,
+        user.id.get() as i64,
+        score,
+        user_id,
+    )
+    .await?;
+    ctx.data().reputation_cache.invalidate_guild(server_id);
+    ctx.data().audit_log.record(
+        server_id,
+        AuditEvent::ReputationOverridden {
+            user_id,
+            target_user_id: user.id.get() as i64,
+            score,
+        },
+    );
+    if GuildSettingsService::get_reputation_weighting(&ctx.data().database, server_id).await? {
+        BoardUIService::refresh_all_board_messages_serenity(ctx.serenity_context(), ctx.data())
+            .await?;
+    }
+
+    let content = match score {
+        Some(score) => format!("✅ <@{}>の信頼度を{}に設定しました。", user.id, score),
+        None => format!(
+            "✅ <@{}>の信頼度の設定を外し、計算値に戻しました。",
+            user.id
+        ),
+    };
+    let rep = ctx
+        .reply_builder(CreateReply::default())
+        .content(content)
+        .ephemeral(true);
+    ctx.send(rep).await?;
+    Ok(())
+}
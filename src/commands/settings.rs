@@ -0,0 +1,291 @@
+use crate::{Context, Error, services::*};
+use poise::serenity_prelude as serenity;
+use poise::{ChoiceParameter, CreateReply};
+
+/// グラフの配色テーマ
+#[derive(Debug, Clone, Copy, poise::ChoiceParameter)]
+pub enum ThemeChoice {
+    #[name = "ライト"]
+    Light,
+    #[name = "ダーク"]
+    Dark,
+}
+
+/// グラフのラベルの言語
+#[derive(Debug, Clone, Copy, poise::ChoiceParameter)]
+pub enum LocaleChoice {
+    #[name = "日本語"]
+    Ja,
+    #[name = "English"]
+    En,
+}
+
+/// タイムライングラフの描画スタイル
+#[derive(Debug, Clone, Copy, poise::ChoiceParameter)]
+pub enum ChartStyleChoice {
+    #[name = "折れ線（累積）"]
+    Line,
+    #[name = "棒（15分ごと）"]
+    Bar,
+    #[name = "積み上げ面（割合）"]
+    StackedArea,
+}
+
+impl ChartStyleChoice {
+    pub fn style(&self) -> ChartStyle {
+        match self {
+            ChartStyleChoice::Line => ChartStyle::Line,
+            ChartStyleChoice::Bar => ChartStyle::Bar,
+            ChartStyleChoice::StackedArea => ChartStyle::StackedArea,
+        }
+    }
+}
+
+/// 掲示板に表示するグラフのスタイルを設定するコマンド
+#[poise::command(slash_command, guild_only, default_member_permissions = "MANAGE_GUILD")]
+pub async fn board_chart_style(
+    ctx: Context<'_>,
+    #[description = "グラフのスタイル"] style: ChartStyleChoice,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or(Error::NotInGuild)?;
+    ctx.defer_ephemeral().await?;
+
+    let updated = BoardService::update_board_chart_style(
+        &ctx.data().database,
+        guild_id.get() as i64,
+        style.style(),
+    )
+    .await?;
+
+    let Some(board_data) = updated else {
+        BoardUIService::handle_empty_board_data(&ctx).await?;
+        return Ok(());
+    };
+
+    // 新しいスタイルで掲示板を更新
+    BoardUIService::update_all_board_messages(&ctx, vec![board_data]).await?;
+
+    let rep = ctx
+        .reply_builder(CreateReply::default())
+        .content(format!(
+            "✅ 掲示板のグラフを「{}」に変更しました。",
+            style.name()
+        ))
+        .ephemeral(true);
+    ctx.send(rep).await?;
+    Ok(())
+}
+
+/// グラフのテーマを設定するコマンド
+#[allow(clippy::too_many_arguments)]
+#[poise::command(slash_command, guild_only, default_member_permissions = "MANAGE_GUILD")]
+pub async fn chart_theme(
+    ctx: Context<'_>,
+    #[description = "配色テーマ"] theme: Option<ThemeChoice>,
+    #[description = "ラベルの言語"] locale: Option<LocaleChoice>,
+    #[description = "フォントファミリー名（例: Noto Sans CJK JP）"] font_family: Option<String>,
+    #[description = "文字サイズの倍率 (0.5〜3.0)"] font_scale: Option<f64>,
+    #[description = "背景色 (#RRGGBB)"] background: Option<String>,
+    #[description = "系列の色 (#RRGGBB,#RRGGBB,#RRGGBB)"] series_colors: Option<String>,
+    #[description = "設定を既定値に戻す"] reset: Option<bool>,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or(Error::NotInGuild)?;
+    let server_id = guild_id.get() as i64;
+
+    if reset.unwrap_or(false) {
+        GuildSettingsService::reset_chart_theme(&ctx.data().database, server_id).await?;
+        ctx.data().chart_cache.invalidate_guild(server_id);
+        let rep = ctx
+            .reply_builder(CreateReply::default())
+            .content("✅ グラフのテーマを既定値に戻しました。")
+            .ephemeral(true);
+        ctx.send(rep).await?;
+        return Ok(());
+    }
+
+    // 入力値の検証
+    let mut errors = Vec::new();
+    if let Some(scale) = font_scale
+        && !(0.5..=3.0).contains(&scale)
+    {
+        errors.push("文字サイズの倍率は0.5〜3.0で指定してください。");
+    }
+    if let Some(background) = &background
+        && ChartTheme::parse_hex_color(background).is_none()
+    {
+        errors.push("背景色は #RRGGBB 形式で指定してください。");
+    }
+    if let Some(series_colors) = &series_colors
+        && ChartTheme::parse_series_colors(series_colors).is_none()
+    {
+        errors.push("系列の色は #RRGGBB をカンマ区切りで3つ指定してください。");
+    }
+    if !errors.is_empty() {
+        let rep = ctx
+            .reply_builder(CreateReply::default())
+            .content(format!("❌ {}", errors.join("\n❌ ")))
+            .ephemeral(true);
+        ctx.send(rep).await?;
+        return Ok(());
+    }
+
+    let mut notes = String::new();
+    if let Some(font_family) = &font_family
+        && !ChartTheme::is_font_available(font_family)
+    {
+        notes.push_str(&format!(
+            "\n⚠️ フォント「{}」がインストールされていないため、代替フォント「{}」が使用されます。",
+            font_family,
+            ChartTheme::resolve_font_family(Some(font_family))
+        ));
+    }
+
+    let update = ChartThemeUpdate {
+        theme: theme.map(|theme| match theme {
+            ThemeChoice::Light => "light".to_string(),
+            ThemeChoice::Dark => "dark".to_string(),
+        }),
+        locale: locale.map(|locale| match locale {
+            LocaleChoice::Ja => ChartLocale::Ja.code().to_string(),
+            LocaleChoice::En => ChartLocale::En.code().to_string(),
+        }),
+        font_family,
+        font_scale,
+        background,
+        series_colors,
+    };
+    let settings =
+        GuildSettingsService::update_chart_theme(&ctx.data().database, server_id, update).await?;
+    ctx.data().chart_cache.invalidate_guild(server_id);
+
+    let rep = ctx
+        .reply_builder(CreateReply::default())
+        .content(format!(
+            "✅ グラフのテーマを保存しました（次回の掲示板更新から反映されます）。\n\
+            テーマ: {}\n\
+            言語: {}\n\
+            フォント: {}\n\
+            文字サイズ倍率: {}\n\
+            背景色: {}\n\
+            系列の色: {}{}",
+            settings.chart_theme,
+            settings.chart_locale,
+            settings.chart_font_family.as_deref().unwrap_or("既定"),
+            settings.chart_font_scale.unwrap_or(1.0),
+            settings.chart_background.as_deref().unwrap_or("既定"),
+            settings.chart_series_colors.as_deref().unwrap_or("既定"),
+            notes
+        ))
+        .ephemeral(true);
+    ctx.send(rep).await?;
+    Ok(())
+}
+
+/// 投票できるユーザーの条件を設定するコマンド
+// オプションを全て省略した場合は現在の条件を表示する
+#[poise::command(slash_command, guild_only, default_member_permissions = "MANAGE_GUILD")]
+pub async fn vote_eligibility(
+    ctx: Context<'_>,
+    #[description = "投票に必要なロール"] required_role: Option<serenity::Role>,
+    #[description = "アカウント作成から必要な日数（0で条件を外す）"]
+    #[max = 3650]
+    min_account_age_days: Option<u32>,
+    #[description = "サーバー参加から必要な日数（0で条件を外す）"]
+    #[max = 3650]
+    min_membership_days: Option<u32>,
+    #[description = "必要なロールの条件を外す"] clear_role: Option<bool>,
+    #[description = "全ての条件を外す"] reset: Option<bool>,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or(Error::NotInGuild)?;
+    let server_id = guild_id.get() as i64;
+    let database = &ctx.data().database;
+
+    let current = GuildSettingsService::get_vote_eligibility(database, Some(server_id)).await?;
+    let unchanged = required_role.is_none()
+        && min_account_age_days.is_none()
+        && min_membership_days.is_none()
+        && clear_role.is_none()
+        && reset.is_none();
+    if unchanged {
+        let rep = ctx
+            .reply_builder(CreateReply::default())
+            .content(format!("🗳️ 現在の投票の条件:\n{}", current.describe()))
+            .ephemeral(true);
+        ctx.send(rep).await?;
+        return Ok(());
+    }
+
+    let mut eligibility = if reset.unwrap_or(false) {
+        VoteEligibility::default()
+    } else {
+        current
+    };
+    if clear_role.unwrap_or(false) {
+        eligibility.required_role_id = None;
+    }
+    if let Some(role) = required_role {
+        eligibility.required_role_id = Some(role.id.get() as i64);
+    }
+    if let Some(days) = min_account_age_days {
+        eligibility.min_account_age_days = (days > 0).then_some(days);
+    }
+    if let Some(days) = min_membership_days {
+        eligibility.min_membership_days = (days > 0).then_some(days);
+    }
+
+    GuildSettingsService::update_vote_eligibility(database, server_id, eligibility).await?;
+
+    let rep = ctx
+        .reply_builder(CreateReply::default())
+        .content(format!(
+            "✅ 投票の条件を保存しました。\n{}",
+            eligibility.describe()
+        ))
+        .ephemeral(true);
+    ctx.send(rep).await?;
+    Ok(())
+}
+
+/// 監査ログを投稿するチャンネルを設定するコマンド
+// オプションを全て省略した場合は現在の設定を表示する
+#[poise::command(slash_command, guild_only, default_member_permissions = "MANAGE_GUILD")]
+pub async fn audit_log_channel(
+    ctx: Context<'_>,
+    #[description = "監査ログを投稿するチャンネル"]
+    #[channel_types("Text")]
+    channel: Option<serenity::GuildChannel>,
+    #[description = "監査ログの投稿を止める"] disable: Option<bool>,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or(Error::NotInGuild)?;
+    let server_id = guild_id.get() as i64;
+    let database = &ctx.data().database;
+
+    let content = if disable.unwrap_or(false) {
+        GuildSettingsService::update_audit_log_channel(database, server_id, None).await?;
+        "✅ 監査ログの投稿を停止しました。".to_string()
+    } else if let Some(channel) = channel {
+        GuildSettingsService::update_audit_log_channel(
+            database,
+            server_id,
+            Some(channel.id.get() as i64),
+        )
+        .await?;
+        format!(
+            "✅ 監査ログを <#{}> に投稿します（投票の変更・掲示板の作成と移動・投票のリセットを{}秒ごとにまとめて投稿します）。",
+            channel.id,
+            ctx.data().config.audit.flush_interval_secs
+        )
+    } else {
+        match GuildSettingsService::get_audit_log_channel(database, server_id).await? {
+            Some(channel_id) => format!("📝 監査ログの投稿先: <#{}>", channel_id),
+            None => "📝 監査ログは投稿していません。".to_string(),
+        }
+    };
+
+    let rep = ctx
+        .reply_builder(CreateReply::default())
+        .content(content)
+        .ephemeral(true);
+    ctx.send(rep).await?;
+    Ok(())
+}
@@ -0,0 +1,175 @@
+use crate::{Context, Error, services::*};
+use poise::{
+    ChoiceParameter, CreateReply,
+    serenity_prelude::{Colour, CreateEmbed, CreateEmbedFooter},
+};
+
+/// ランキングに表示する人数
+const LEADERBOARD_LIMIT: usize = 10;
+
+/// 統計を集計する範囲
+#[derive(Debug, Clone, Copy, poise::ChoiceParameter)]
+pub enum StatsWindowChoice {
+    #[name = "今週"]
+    Weekly,
+    #[name = "全期間"]
+    AllTime,
+}
+
+impl StatsWindowChoice {
+    pub fn window(&self) -> StatsWindow {
+        match self {
+            StatsWindowChoice::Weekly => StatsWindow::Weekly,
+            StatsWindowChoice::AllTime => StatsWindow::AllTime,
+        }
+    }
+}
+
+/// ランキングの並び順
+#[derive(Debug, Clone, Copy, poise::ChoiceParameter)]
+pub enum LeaderboardSortChoice {
+    #[name = "報告数"]
+    Reports,
+    #[name = "一番乗り"]
+    FirstFound,
+    #[name = "的中率"]
+    Accuracy,
+    #[name = "連続日数"]
+    Streak,
+}
+
+impl LeaderboardSortChoice {
+    pub fn sort(&self) -> LeaderboardSort {
+        match self {
+            LeaderboardSortChoice::Reports => LeaderboardSort::Reports,
+            LeaderboardSortChoice::FirstFound => LeaderboardSort::FirstFound,
+            LeaderboardSortChoice::Accuracy => LeaderboardSort::Accuracy,
+            LeaderboardSortChoice::Streak => LeaderboardSort::Streak,
+        }
+    }
+}
+
+/// 投票の統計を表示するコマンド
+#[poise::command(slash_command, guild_only, subcommands("me"), subcommand_required)]
+pub async fn stats(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// このサーバーでの自分の投票の統計を表示する
+#[poise::command(slash_command, guild_only)]
+pub async fn me(
+    ctx: Context<'_>,
+    #[description = "集計する範囲（省略時は全期間）"] window: Option<StatsWindowChoice>,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or(Error::NotInGuild)?;
+    let window = window.map_or(StatsWindow::AllTime, |window| window.window());
+    let user_id = ctx.author().id.get() as i64;
+
+    let stats =
+        StatsService::get_guild_stats(&ctx.data().database, guild_id.get() as i64, window).await?;
+    let user = stats
+        .into_iter()
+        .find(|user| user.user_id == user_id)
+        .unwrap_or(UserStats {
+            user_id,
+            ..Default::default()
+        });
+
+    let accuracy = match user.accuracy() {
+        Some(accuracy) => format!(
+            "{:.0}%（{}/{}期間）",
+            accuracy * 100.0,
+            user.agreed_periods,
+            user.judged_periods
+        ),
+        None => "判定できる期間がありません".to_string(),
+    };
+    let embed = CreateEmbed::new()
+        .title(format!("📈 あなたの投票の統計（{}）", window.label()))
+        .field("報告数", format!("{}回", user.reports), true)
+        .field(
+            "「営業してる」の一番乗り",
+            format!("{}回", user.first_found),
+            true,
+        )
+        .field("多数派との一致率", accuracy, true)
+        .field(
+            "連続投票",
+            format!(
+                "現在 {}日 / 最長 {}日",
+                user.current_streak, user.best_streak
+            ),
+            true,
+        )
+        .colour(Colour::from_rgb(52, 152, 219));
+
+    let rep = ctx
+        .reply_builder(CreateReply::default())
+        .embed(embed)
+        .ephemeral(true);
+    ctx.send(rep).await?;
+    Ok(())
+}
+
+/// このサーバーの投票のランキングを表示するコマンド
+#[poise::command(slash_command, guild_only)]
+pub async fn leaderboard(
+    ctx: Context<'_>,
+    #[description = "並び順（省略時は報告数）"] sort: Option<LeaderboardSortChoice>,
+    #[description = "集計する範囲（省略時は今週）"] window: Option<StatsWindowChoice>,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or(Error::NotInGuild)?;
+    let sort = sort.unwrap_or(LeaderboardSortChoice::Reports);
+    let window = window.map_or(StatsWindow::Weekly, |window| window.window());
+
+    let stats =
+        StatsService::get_guild_stats(&ctx.data().database, guild_id.get() as i64, window).await?;
+    let ranked = StatsService::rank(stats, sort.sort(), LEADERBOARD_LIMIT);
+
+    let description = if ranked.is_empty() {
+        "まだランキングに載るユーザーがいません。".to_string()
+    } else {
+        ranked
+            .iter()
+            .enumerate()
+            .map(|(index, user)| {
+                let value = match sort {
+                    LeaderboardSortChoice::Reports => format!("{}回", user.reports),
+                    LeaderboardSortChoice::FirstFound => format!("{}回", user.first_found),
+                    LeaderboardSortChoice::Accuracy => format!(
+                        "{:.0}%（{}/{}期間）",
+                        user.accuracy().unwrap_or_default() * 100.0,
+                        user.agreed_periods,
+                        user.judged_periods
+                    ),
+                    LeaderboardSortChoice::Streak => format!(
+                        "最長 {}日（現在 {}日）",
+                        user.best_streak, user.current_streak
+                    ),
+                };
+                format!("**{}.** <@{}> {}", index + 1, user.user_id, value)
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    let mut embed = CreateEmbed::new()
+        .title(format!(
+            "🏆 {}のランキング（{}）",
+            sort.name(),
+            window.label()
+        ))
+        .description(description)
+        .colour(Colour::from_rgb(241, 196, 15));
+    if matches!(sort, LeaderboardSortChoice::Accuracy) {
+        embed = embed.footer(CreateEmbedFooter::new(format!(
+            "多数派が決まった期間が{}以上あるユーザーのみ",
+            MIN_ACCURACY_PERIODS
+        )));
+    }
+
+    // Embed内のメンションは通知されないため、ランキングはチャンネルに表示する
+    let rep = ctx.reply_builder(CreateReply::default()).embed(embed);
+    ctx.send(rep).await?;
+    Ok(())
+}
@@ -1,15 +1,44 @@
+use super::settings::ChartStyleChoice;
 use crate::entities::vote::Model as VoteModel;
 use crate::{Context, Error, services::*};
+use chrono::{Days, NaiveDate};
 use poise::{
     CreateReply,
-    serenity_prelude::{Colour, CreateEmbed},
+    serenity_prelude::{Colour, CreateAttachment, CreateEmbed},
 };
+use tracing::error;
 
 /// 投票をリセットするコマンド
-#[poise::command(slash_command)]
+// 投票はサーバーをまたいで1つなので、全てのサーバーの投票が消える。サーバーの管理者だけが使える
+#[poise::command(
+    slash_command,
+    guild_only,
+    default_member_permissions = "MANAGE_GUILD",
+    required_permissions = "MANAGE_GUILD"
+)]
 pub async fn reset_votes(ctx: Context<'_>) -> Result<(), Error> {
-    match VoteService::delete_all_vote(&ctx.data().database).await {
-        Ok(result) => {
+    // 投票が消えるサーバー全ての監査ログに残すため、削除する前にサーバーごとの件数を数える
+    let guild_counts = VoteService::count_votes_by_guild(&ctx.data().database).await;
+    let result = match guild_counts {
+        Ok(guild_counts) => VoteService::delete_all_vote(&ctx.data().database)
+            .await
+            .map(|result| (guild_counts, result)),
+        Err(e) => Err(e),
+    };
+
+    match result {
+        Ok((guild_counts, result)) => {
+            ctx.data().chart_cache.invalidate();
+            for (guild_id, deleted) in guild_counts {
+                ctx.data().audit_log.record(
+                    guild_id,
+                    AuditEvent::VotesReset {
+                        user_id: ctx.author().id.get() as i64,
+                        deleted,
+                    },
+                );
+            }
+
             let rep = ctx
                 .reply_builder(CreateReply::default())
                 .content(format!(
@@ -20,7 +49,7 @@ pub async fn reset_votes(ctx: Context<'_>) -> Result<(), Error> {
             ctx.send(rep).await?;
         }
         Err(e) => {
-            eprintln!("投票のリセット中にエラーが発生しました: {}", e);
+            error!(error = %e, "投票のリセット中にエラーが発生しました");
             let rep = ctx
                 .reply_builder(CreateReply::default())
                 .content("❌ 投票のリセットに失敗しました。")
@@ -31,38 +60,121 @@ pub async fn reset_votes(ctx: Context<'_>) -> Result<(), Error> {
     Ok(())
 }
 
+/// 自分の投票を操作するコマンド
+#[poise::command(slash_command, subcommands("retract", "mine"), subcommand_required)]
+pub async fn vote(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// 現在の期間の自分の投票を取り消す
+#[poise::command(slash_command)]
+pub async fn retract(ctx: Context<'_>) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    // 前の期間の投票を取り消さないよう、先に日付チェックを行う
+    if let Err(e) =
+        VoteService::check_reset_and_update_board_if_new_day(ctx.data(), ctx.serenity_context())
+            .await
+    {
+        error!(error = %e, "日付チェック中にエラーが発生しました");
+    }
+
+    let outcome = VoteService::retract_user_vote(
+        ctx.data(),
+        ctx.author().id.get() as i64,
+        ctx.guild_id().map(|id| id.get() as i64),
+    )
+    .await?;
+
+    let rep = ctx
+        .reply_builder(CreateReply::default())
+        .content(outcome.message())
+        .ephemeral(true);
+    ctx.send(rep).await?;
+
+    if matches!(outcome, RetractOutcome::Retracted { .. }) {
+        BoardUIService::refresh_all_board_messages_serenity(ctx.serenity_context(), ctx.data())
+            .await?;
+    }
+    Ok(())
+}
+
+/// 自分の現在の投票と今週の投票を確認する
+#[poise::command(slash_command)]
+pub async fn mine(ctx: Context<'_>) -> Result<(), Error> {
+    // 前の期間の投票を表示しないよう、先に日付チェックを行う
+    if let Err(e) = VoteService::check_and_reset_votes_if_new_day(&ctx.data().database).await {
+        error!(error = %e, "日付チェック中にエラーが発生しました");
+    }
+
+    let embed =
+        BoardUIService::create_my_vote_embed(&ctx.data().database, ctx.author().id.get() as i64)
+            .await?;
+    let rep = ctx
+        .reply_builder(CreateReply::default())
+        .embed(embed)
+        .ephemeral(true);
+    ctx.send(rep).await?;
+    Ok(())
+}
+
 /// 投票結果を確認するコマンド
 #[poise::command(slash_command)]
 pub async fn vote_results(ctx: Context<'_>) -> Result<(), Error> {
     // 日付チェックを行い、必要に応じて投票をリセット
     if let Err(e) = VoteService::check_and_reset_votes_if_new_day(&ctx.data().database).await {
-        eprintln!("日付チェック中にエラーが発生しました: {}", e);
+        error!(error = %e, "日付チェック中にエラーが発生しました");
     }
 
-    let found_count = VoteService::count_votes_by_action(&ctx.data().database, "found".to_string())
-        .await
-        .unwrap_or(0);
-    let not_found_count =
-        VoteService::count_votes_by_action(&ctx.data().database, "not_found".to_string())
-            .await
-            .unwrap_or(0);
-    let sold_out_count =
-        VoteService::count_votes_by_action(&ctx.data().database, "sold_out".to_string())
-            .await
-            .unwrap_or(0);
+    // サーバーではこのサーバーの投票を、重み付けが有効なら信頼度で重み付けした票数を表示する
+    // サーバーの外（DM）では全サーバーの投票を数える
+    let database = &ctx.data().database;
+    let weighted = match ctx.guild_id() {
+        Some(guild_id) => {
+            ReputationService::get_weighted_tally(
+                database,
+                &ctx.data().reputation_cache,
+                guild_id.get() as i64,
+            )
+            .await?
+        }
+        None => None,
+    };
+
+    let (title, description) = match weighted {
+        Some(tally) => (
+            board_ui_service::WEIGHTED_TALLY_TITLE.to_string(),
+            format!("{}\n\n合計: {:.1}票", tally.describe(), tally.total()),
+        ),
+        None => {
+            let (title, counts) = match ctx.guild_id() {
+                Some(guild_id) => (
+                    "📊 現在の投票結果",
+                    VoteService::count_guild_votes(database, guild_id.get() as i64).await?,
+                ),
+                None => {
+                    let mut counts = Vec::new();
+                    for option in &VOTE_OPTIONS {
+                        counts.push(
+                            VoteService::count_votes_by_action(database, option.action.to_string())
+                                .await?,
+                        );
+                    }
+                    ("📊 現在の投票結果（全サーバー）", counts)
+                }
+            };
+            let mut description = String::new();
+            for (option, count) in VOTE_OPTIONS.iter().zip(&counts) {
+                description.push_str(&format!("{} {}: {}票\n", option.emoji, option.label, count));
+            }
+            description.push_str(&format!("\n合計: {}票", counts.iter().sum::<u64>()));
+            (title.to_string(), description)
+        }
+    };
 
     let embed = CreateEmbed::new()
-        .title("📊 現在の投票結果")
-        .description(format!(
-            "🥙 営業してる: {}票\n\
-            ❌ いない: {}票\n\
-            🚫 売り切れた: {}票\n\n\
-            合計: {}票",
-            found_count,
-            not_found_count,
-            sold_out_count,
-            found_count + not_found_count + sold_out_count
-        ))
+        .title(title)
+        .description(description)
         .colour(Colour::from_rgb(52, 152, 219))
         .timestamp(chrono::Utc::now());
 
@@ -71,14 +183,106 @@ pub async fn vote_results(ctx: Context<'_>) -> Result<(), Error> {
     Ok(())
 }
 
+/// 期間比較グラフで比較する投票の種類
+#[derive(Debug, Clone, Copy, poise::ChoiceParameter)]
+pub enum ChartAction {
+    #[name = "営業してる"]
+    Found,
+    #[name = "いない"]
+    NotFound,
+    #[name = "売り切れた"]
+    SoldOut,
+}
+
+impl ChartAction {
+    /// 対応する投票の選択肢の定義
+    fn option(&self) -> &'static VoteOption {
+        match self {
+            ChartAction::Found => &VOTE_OPTIONS[0],
+            ChartAction::NotFound => &VOTE_OPTIONS[1],
+            ChartAction::SoldOut => &VOTE_OPTIONS[2],
+        }
+    }
+}
+
+/// グラフの出力形式
+#[derive(Debug, Clone, Copy, poise::ChoiceParameter)]
+pub enum ChartFormatChoice {
+    #[name = "PNG"]
+    Png,
+    #[name = "SVG"]
+    Svg,
+}
+
+/// グラフの大きさ
+#[derive(Debug, Clone, Copy, poise::ChoiceParameter)]
+pub enum ChartSizeChoice {
+    #[name = "小 (800×400)"]
+    Small,
+    #[name = "中 (1200×600)"]
+    Medium,
+    #[name = "大 (1800×900)"]
+    Large,
+}
+
+impl ChartSizeChoice {
+    fn dimensions(&self) -> (u32, u32) {
+        match self {
+            ChartSizeChoice::Small => (800, 400),
+            ChartSizeChoice::Medium => (1200, 600),
+            ChartSizeChoice::Large => (1800, 900),
+        }
+    }
+}
+
+/// 期間比較グラフに重ねる期間の上限
+const MAX_COMPARISON_PERIODS: usize = 14;
+
 /// 投票結果のグラフを生成するコマンド
+// 開始日を指定すると、その日から終了日までの各期間の累積投票数を重ねて比較する
+#[allow(clippy::too_many_arguments)]
 #[poise::command(slash_command)]
-pub async fn vote_chart(ctx: Context<'_>) -> Result<(), Error> {
+pub async fn vote_chart(
+    ctx: Context<'_>,
+    #[description = "比較を開始する期間の日付 (YYYY-MM-DD)"] start_date: Option<String>,
+    #[description = "比較を終了する期間の日付 (YYYY-MM-DD、省略時は今日)"] end_date: Option<String>,
+    #[description = "終了日と同じ曜日の期間だけを比較する"] same_weekday: Option<bool>,
+    #[description = "比較する投票の種類（省略時は「営業してる」）"] action: Option<ChartAction>,
+    #[description = "グラフのスタイル（省略時は設定の既定値、期間比較では無視されます）"]
+    style: Option<ChartStyleChoice>,
+    #[description = "出力形式（省略時はPNG）"] format: Option<ChartFormatChoice>,
+    #[description = "グラフの大きさ（省略時は設定の既定値）"] size: Option<ChartSizeChoice>,
+    #[description = "PNGの解像度の倍率（高DPI向け）"]
+    #[min = 1]
+    #[max = 3]
+    scale: Option<u32>,
+) -> Result<(), Error> {
     ctx.defer().await?;
 
+    let chart_config = ctx.data().config.chart;
+    let (width, height) = size
+        .map(|size| size.dimensions())
+        .unwrap_or((chart_config.width, chart_config.height));
+    let output = match format.unwrap_or(ChartFormatChoice::Png) {
+        ChartFormatChoice::Png => ChartOutput::png(width, height, scale.unwrap_or(1)),
+        ChartFormatChoice::Svg => ChartOutput::svg(width, height),
+    };
+
     // 日付チェックを行い、必要に応じて投票をリセット
     if let Err(e) = VoteService::check_and_reset_votes_if_new_day(&ctx.data().database).await {
-        eprintln!("日付チェック中にエラーが発生しました: {}", e);
+        error!(error = %e, "日付チェック中にエラーが発生しました");
+    }
+
+    if let Some(start_date) = start_date {
+        return vote_comparison_chart(
+            ctx,
+            &start_date,
+            end_date.as_deref(),
+            same_weekday.unwrap_or(false),
+            action.unwrap_or(ChartAction::Found),
+            &output,
+        )
+        .await;
     }
 
     // 投票データを取得
@@ -89,12 +293,23 @@ pub async fn vote_chart(ctx: Context<'_>) -> Result<(), Error> {
         return Ok(());
     }
 
-    // 時系列グラフを生成
-    let timeline_path = "vote_timeline.png";
-    match ChartService::generate_vote_timeline_chart(votes, timeline_path).await {
-        Ok(_) => {
+    // サーバーのテーマで時系列グラフを生成（同じデータで描画済みならキャッシュを使う）
+    let guild_id = ctx.guild_id().map(|id| id.get() as i64);
+    match ChartService::get_or_generate_timeline_chart(
+        &ctx.data().database,
+        &ctx.data().chart_cache,
+        guild_id,
+        style
+            .map(|style| style.style())
+            .unwrap_or(chart_config.style),
+        &output,
+    )
+    .await
+    {
+        Ok(chart) => {
             // ファイルを送信
-            let file = poise::serenity_prelude::CreateAttachment::path(timeline_path).await?;
+            let file =
+                CreateAttachment::bytes(chart.as_ref().clone(), output.filename("vote_timeline"));
             let rep = ctx
                 .reply_builder(CreateReply::default())
                 .content("📈 **投票の時系列グラフ**")
@@ -102,7 +317,102 @@ pub async fn vote_chart(ctx: Context<'_>) -> Result<(), Error> {
             ctx.send(rep).await?;
         }
         Err(e) => {
-            eprintln!("グラフ生成エラー: {}", e);
+            error!(error = %e, "グラフ生成エラー");
+            ctx.say("❌ グラフの生成に失敗しました。").await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// 複数期間の累積投票数を重ねた比較グラフを送信する
+async fn vote_comparison_chart(
+    ctx: Context<'_>,
+    start_date: &str,
+    end_date: Option<&str>,
+    same_weekday: bool,
+    action: ChartAction,
+    output: &ChartOutput,
+) -> Result<(), Error> {
+    let current_period = VoteService::get_current_jst_afternoon_period();
+
+    let Ok(start) = NaiveDate::parse_from_str(start_date, "%Y-%m-%d") else {
+        ctx.say("❌ 開始日は YYYY-MM-DD 形式で指定してください。")
+            .await?;
+        return Ok(());
+    };
+    let end = match end_date {
+        Some(end_date) => match NaiveDate::parse_from_str(end_date, "%Y-%m-%d") {
+            Ok(end) => end,
+            Err(_) => {
+                ctx.say("❌ 終了日は YYYY-MM-DD 形式で指定してください。")
+                    .await?;
+                return Ok(());
+            }
+        },
+        None => current_period,
+    };
+    if start > end {
+        ctx.say("❌ 開始日は終了日以前の日付を指定してください。")
+            .await?;
+        return Ok(());
+    }
+
+    // 日付を並べる前に件数を求めて、長すぎる範囲を断る
+    // 同じ曜日だけの場合は終了日から7日ずつ遡った日付を比較する
+    let days = (end - start).num_days();
+    let period_count = if same_weekday { days / 7 + 1 } else { days + 1 };
+    if period_count > MAX_COMPARISON_PERIODS as i64 {
+        ctx.say(format!(
+            "❌ 比較できる期間は最大{}件です（指定された期間: {}件）。",
+            MAX_COMPARISON_PERIODS, period_count
+        ))
+        .await?;
+        return Ok(());
+    }
+
+    let step = if same_weekday { 7 } else { 1 };
+    let dates: Vec<NaiveDate> = (0..period_count)
+        .rev()
+        .map(|back| end - Days::new((back * step) as u64))
+        .collect();
+
+    // 現在の期間は投票テーブル、過去の期間は投票履歴から取得する
+    let mut periods: Vec<(NaiveDate, Vec<VoteModel>)> = Vec::new();
+    for date in dates {
+        let votes = if date == current_period {
+            VoteService::get_all_votes(&ctx.data().database).await?
+        } else {
+            VoteService::get_period_final_votes(&ctx.data().database, date).await?
+        };
+        if !votes.is_empty() {
+            periods.push((date, votes));
+        }
+    }
+
+    if periods.is_empty() {
+        ctx.say("📊 指定された期間の投票データがありません。")
+            .await?;
+        return Ok(());
+    }
+
+    let guild_id = ctx.guild_id().map(|id| id.get() as i64);
+    let theme = GuildSettingsService::get_chart_theme(&ctx.data().database, guild_id).await?;
+    let option = action.option();
+    match ChartService::generate_vote_comparison_chart(periods, option, &theme, output).await {
+        Ok(chart) => {
+            let file = CreateAttachment::bytes(chart, output.filename("vote_comparison"));
+            let rep = ctx
+                .reply_builder(CreateReply::default())
+                .content(format!(
+                    "📈 **「{}」の期間比較グラフ** ({} 〜 {})",
+                    option.label, start, end
+                ))
+                .attachment(file);
+            ctx.send(rep).await?;
+        }
+        Err(e) => {
+            error!(error = %e, "比較グラフ生成エラー");
             ctx.say("❌ グラフの生成に失敗しました。").await?;
         }
     }
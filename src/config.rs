@@ -0,0 +1,643 @@
+use crate::database::DEFAULT_DATABASE_URL;
+use crate::services::ChartStyle;
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value as JsonValue};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use toml_edit::{DocumentMut, Value};
+use tracing_subscriber::EnvFilter;
+
+/// 設定ファイルの既定のパス（存在しなければ既定値と環境変数のみを使う）
+pub const DEFAULT_CONFIG_PATH: &str = "config.toml";
+/// 報告の写真として設定できる最大サイズ（Botが添付できるファイルの上限）
+const MAX_IMAGE_SIZE_BYTES: u32 = 25 * 1024 * 1024;
+
+/// Botと管理ツールの設定
+///
+/// 既定値、設定ファイル（TOML）、環境変数の順に上書きして読み込む。
+/// 環境変数は`DISCORD_TOKEN`と`DATABASE_URL`のほか、`KEBAB_<セクション>_<キー>`
+/// （例: `KEBAB_BOARD_CHECK_INTERVAL_SECS`）で全ての項目を上書きできる。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub discord: DiscordConfig,
+    pub database: DatabaseConfig,
+    pub period: PeriodConfig,
+    pub board: BoardConfig,
+    pub vote: VoteConfig,
+    pub audit: AuditConfig,
+    pub chart: ChartConfig,
+    pub image: ImageConfig,
+    pub features: FeatureConfig,
+    pub log: LogConfig,
+    pub http: HttpConfig,
+    pub shutdown: ShutdownConfig,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DiscordConfig {
+    /// Botのトークン（管理ツールでは不要）
+    pub token: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DatabaseConfig {
+    pub url: String,
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        Self {
+            url: DEFAULT_DATABASE_URL.to_string(),
+        }
+    }
+}
+
+/// 投票期間の区切り方
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PeriodConfig {
+    /// 投票期間を判定するタイムゾーン
+    #[serde(with = "timezone")]
+    pub timezone: Tz,
+    /// この時刻（時）より前の投票は前日の期間として扱う
+    pub rollover_hour: u32,
+}
+
+impl Default for PeriodConfig {
+    fn default() -> Self {
+        Self {
+            timezone: chrono_tz::Asia::Tokyo,
+            rollover_hour: 12,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BoardConfig {
+    /// 投票期間の切り替えを確認する間隔（秒）
+    pub check_interval_secs: u64,
+    /// 複数の掲示板を更新する際の間隔（ミリ秒、レート制限対策）
+    pub edit_interval_ms: u64,
+}
+
+impl Default for BoardConfig {
+    fn default() -> Self {
+        Self {
+            check_interval_secs: 3600,
+            edit_interval_ms: 500,
+        }
+    }
+}
+
+/// 投票ボタンの連打対策
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct VoteConfig {
+    /// 投票してから次に投票を変更できるまでの時間（秒、0なら制限しない）
+    pub change_cooldown_secs: u64,
+    /// 報告してから次に報告できるまでの時間（秒、0なら制限しない）
+    pub report_cooldown_secs: u64,
+}
+
+impl Default for VoteConfig {
+    fn default() -> Self {
+        Self {
+            change_cooldown_secs: 30,
+            report_cooldown_secs: 60,
+        }
+    }
+}
+
+/// 監査ログの投稿
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AuditConfig {
+    /// 溜まった監査ログをまとめて投稿する間隔（秒、レート制限対策）
+    pub flush_interval_secs: u64,
+}
+
+impl Default for AuditConfig {
+    fn default() -> Self {
+        Self {
+            flush_interval_secs: 10,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ChartConfig {
+    /// `/vote_chart`でスタイルを省略した場合のスタイル
+    pub style: ChartStyle,
+    pub width: u32,
+    pub height: u32,
+    /// グラフのX軸に表示する時間帯（開始時は含み終了時は含まない）
+    pub start_hour: u32,
+    pub end_hour: u32,
+}
+
+impl Default for ChartConfig {
+    fn default() -> Self {
+        Self {
+            style: ChartStyle::Line,
+            width: 1200,
+            height: 600,
+            start_hour: 14,
+            end_hour: 20,
+        }
+    }
+}
+
+/// 報告に添付する写真
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ImageConfig {
+    /// 受け付ける写真の最大サイズ（バイト）
+    pub max_size_bytes: u32,
+}
+
+impl Default for ImageConfig {
+    fn default() -> Self {
+        Self {
+            max_size_bytes: 8 * 1024 * 1024,
+        }
+    }
+}
+
+/// 機能ごとの有効・無効
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FeatureConfig {
+    /// 掲示板にタイムライングラフを添付する
+    pub board_chart: bool,
+    /// `/export`コマンドを登録する
+    pub export: bool,
+    /// `/import_history`コマンドを登録する
+    pub import: bool,
+}
+
+impl Default for FeatureConfig {
+    fn default() -> Self {
+        Self {
+            board_chart: true,
+            export: true,
+            import: true,
+        }
+    }
+}
+
+/// ログの出力方法
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LogConfig {
+    /// 出力するログのレベル（`RUST_LOG`と同じ書式、例: `info,kebab_bot=debug`）
+    pub level: String,
+    pub format: LogFormat,
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        Self {
+            level: "info,sqlx=warn".to_string(),
+            format: LogFormat::Text,
+        }
+    }
+}
+
+/// 監視用のHTTPサーバー
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HttpConfig {
+    /// `/metrics`などのエンドポイントを公開する
+    pub enabled: bool,
+    /// 待ち受けるアドレス
+    pub listen: SocketAddr,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            listen: SocketAddr::from(([127, 0, 0, 1], 9090)),
+        }
+    }
+}
+
+/// 終了時の処理
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ShutdownConfig {
+    /// 終了シグナルを受け取ってから処理中の作業の完了を待つ最大の時間（秒）
+    pub timeout_secs: u64,
+}
+
+impl Default for ShutdownConfig {
+    fn default() -> Self {
+        Self { timeout_secs: 30 }
+    }
+}
+
+/// ログの出力形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// 人が読むためのテキスト
+    Text,
+    /// 1行に1つのJSON（ログ収集基盤への転送用）
+    Json,
+}
+
+/// タイムゾーンをIANAの名前（例: `Asia/Tokyo`）で読み書きする
+mod timezone {
+    use chrono_tz::Tz;
+    use serde::{Deserialize, Deserializer, Serializer, de::Error};
+
+    pub fn serialize<S: Serializer>(timezone: &Tz, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(timezone.name())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Tz, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        name.parse().map_err(|_| {
+            D::Error::custom(format!(
+                "「{}」は不明なタイムゾーンです（例: Asia/Tokyo）",
+                name
+            ))
+        })
+    }
+}
+
+/// 設定の読み込みに失敗した理由（見つかった問題を全て含む）
+#[derive(Debug)]
+pub struct ConfigError {
+    pub source: String,
+    pub problems: Vec<String>,
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "設定に問題があります（{}）:", self.source)?;
+        for problem in &self.problems {
+            write!(f, "\n  - {}", problem)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl Config {
+    /// `KEBAB_CONFIG`で指定された設定ファイル（未指定なら`config.toml`）と環境変数から設定を読み込む
+    pub fn load() -> Result<Self, ConfigError> {
+        match std::env::var("KEBAB_CONFIG") {
+            Ok(path) => Self::load_from(Some(Path::new(&path))),
+            Err(_) => {
+                let path = PathBuf::from(DEFAULT_CONFIG_PATH);
+                Self::load_from(path.exists().then_some(path.as_path()))
+            }
+        }
+    }
+
+    /// 指定した設定ファイル（`None`なら既定値のみ）と環境変数から設定を読み込む
+    pub fn load_from(path: Option<&Path>) -> Result<Self, ConfigError> {
+        let source = path
+            .map(|path| path.display().to_string())
+            .unwrap_or_else(|| "環境変数".to_string());
+        let error = |problems: Vec<String>| ConfigError {
+            source: source.clone(),
+            problems,
+        };
+
+        let content = match path {
+            Some(path) => Some(
+                std::fs::read_to_string(path)
+                    .map_err(|e| error(vec![format!("設定ファイルを読み込めません: {}", e)]))?,
+            ),
+            None => None,
+        };
+        Self::parse(content.as_deref(), |name| std::env::var(name).ok()).map_err(error)
+    }
+
+    /// 設定ファイルの内容（`None`なら既定値のみ）と、`env`で引いた環境変数から設定を作る
+    /// 見つかった問題は全て集めて返す
+    fn parse(
+        content: Option<&str>,
+        env: impl Fn(&str) -> Option<String>,
+    ) -> Result<Self, Vec<String>> {
+        let defaults = match serde_json::to_value(Self::default()) {
+            Ok(JsonValue::Object(defaults)) => defaults,
+            _ => unreachable!("設定は常にJSONのオブジェクトに変換できる"),
+        };
+
+        let mut problems = Vec::new();
+        let mut values = match content {
+            Some(content) => Self::read_file(content, &defaults, &mut problems)?,
+            None => Map::new(),
+        };
+        Self::read_env(&mut values, &defaults, env);
+
+        // 項目ごとに読み込んで、どの項目のどの値が正しくないかを全て報告する
+        for (section, table) in &values {
+            let JsonValue::Object(table) = table else {
+                continue;
+            };
+            for (key, value) in table {
+                let single = serde_json::json!({ section: { key: value } });
+                if let Err(e) = serde_json::from_value::<Self>(single) {
+                    problems.push(format!("{}.{} の値が正しくありません: {}", section, key, e));
+                }
+            }
+        }
+        if !problems.is_empty() {
+            return Err(problems);
+        }
+
+        let mut config: Self =
+            serde_json::from_value(JsonValue::Object(values)).map_err(|e| vec![e.to_string()])?;
+        config.discord.token = config.discord.token.filter(|token| !token.is_empty());
+        config.validate(&mut problems);
+
+        if problems.is_empty() {
+            Ok(config)
+        } else {
+            Err(problems)
+        }
+    }
+
+    /// 設定ファイルを読み込んでセクションごとの値にする
+    /// 不明なセクションや項目は`problems`に追加して読み飛ばす
+    fn read_file(
+        content: &str,
+        defaults: &Map<String, JsonValue>,
+        problems: &mut Vec<String>,
+    ) -> Result<Map<String, JsonValue>, Vec<String>> {
+        let document: DocumentMut = content
+            .parse()
+            .map_err(|e| vec![format!("TOMLとして読み込めません: {}", e)])?;
+
+        let mut values = Map::new();
+        for (section, item) in document.iter() {
+            let Some(JsonValue::Object(known)) = defaults.get(section) else {
+                problems.push(format!("不明なセクションです: [{}]", section));
+                continue;
+            };
+            let Some(table) = item.as_table_like() else {
+                problems.push(format!(
+                    "`{}` はセクション（[{}]）で指定してください",
+                    section, section
+                ));
+                continue;
+            };
+
+            let mut section_values = Map::new();
+            for (key, item) in table.iter() {
+                if !known.contains_key(key) {
+                    problems.push(format!("不明な設定項目です: {}.{}", section, key));
+                    continue;
+                }
+                match item.as_value() {
+                    Some(value) => {
+                        section_values.insert(key.to_string(), Self::to_json(value));
+                    }
+                    None => problems.push(format!("{}.{} の値が正しくありません", section, key)),
+                }
+            }
+            values.insert(section.to_string(), JsonValue::Object(section_values));
+        }
+
+        Ok(values)
+    }
+
+    /// TOMLの値をserdeで読み込めるようにJSONの値に変換する
+    fn to_json(value: &Value) -> JsonValue {
+        match value {
+            Value::String(value) => JsonValue::from(value.value().as_str()),
+            Value::Integer(value) => JsonValue::from(*value.value()),
+            Value::Float(value) => JsonValue::from(*value.value()),
+            Value::Boolean(value) => JsonValue::from(*value.value()),
+            Value::Datetime(value) => JsonValue::from(value.value().to_string()),
+            Value::Array(array) => array.iter().map(Self::to_json).collect(),
+            Value::InlineTable(table) => JsonValue::Object(
+                table
+                    .iter()
+                    .map(|(key, value)| (key.to_string(), Self::to_json(value)))
+                    .collect(),
+            ),
+        }
+    }
+
+    /// 環境変数で設定ファイルの値を上書きする
+    /// 環境変数は文字列なので、既定値と同じ型（整数・真偽値）として読み取れる場合はその型にする
+    fn read_env(
+        values: &mut Map<String, JsonValue>,
+        defaults: &Map<String, JsonValue>,
+        env: impl Fn(&str) -> Option<String>,
+    ) {
+        for (section, known) in defaults {
+            let JsonValue::Object(known) = known else {
+                continue;
+            };
+            for (key, default) in known {
+                let name = match (section.as_str(), key.as_str()) {
+                    ("discord", "token") => "DISCORD_TOKEN".to_string(),
+                    ("database", "url") => "DATABASE_URL".to_string(),
+                    _ => format!("KEBAB_{}_{}", section, key).to_uppercase(),
+                };
+                let Some(value) = env(&name) else {
+                    continue;
+                };
+
+                let value = match default {
+                    JsonValue::Number(_) => value
+                        .trim()
+                        .parse::<i64>()
+                        .map(JsonValue::from)
+                        .unwrap_or(JsonValue::String(value)),
+                    JsonValue::Bool(_) => match value.trim().to_lowercase().as_str() {
+                        "true" | "1" | "yes" | "on" => JsonValue::Bool(true),
+                        "false" | "0" | "no" | "off" => JsonValue::Bool(false),
+                        _ => JsonValue::String(value),
+                    },
+                    _ => JsonValue::String(value),
+                };
+                let table = values
+                    .entry(section.clone())
+                    .or_insert_with(|| JsonValue::Object(Map::new()));
+                if let JsonValue::Object(table) = table {
+                    table.insert(key.clone(), value);
+                }
+            }
+        }
+    }
+
+    /// 値の範囲や組み合わせを検証する
+    fn validate(&self, problems: &mut Vec<String>) {
+        if self.period.rollover_hour > 23 {
+            problems.push("period.rollover_hour は0〜23で指定してください".to_string());
+        }
+        if self.board.check_interval_secs < 10 {
+            problems.push("board.check_interval_secs は10秒以上で指定してください".to_string());
+        }
+        if self.board.edit_interval_ms > 60_000 {
+            problems.push("board.edit_interval_ms は60000ミリ秒以下で指定してください".to_string());
+        }
+        if self.vote.change_cooldown_secs > 3600 {
+            problems.push("vote.change_cooldown_secs は3600秒以下で指定してください".to_string());
+        }
+        if self.vote.report_cooldown_secs > 3600 {
+            problems.push("vote.report_cooldown_secs は3600秒以下で指定してください".to_string());
+        }
+        if !(1..=3600).contains(&self.audit.flush_interval_secs) {
+            problems.push("audit.flush_interval_secs は1〜3600秒で指定してください".to_string());
+        }
+        if !(1..=600).contains(&self.shutdown.timeout_secs) {
+            problems.push("shutdown.timeout_secs は1〜600秒で指定してください".to_string());
+        }
+        if !(1..=MAX_IMAGE_SIZE_BYTES).contains(&self.image.max_size_bytes) {
+            problems.push(format!(
+                "image.max_size_bytes は1〜{}バイトで指定してください",
+                MAX_IMAGE_SIZE_BYTES
+            ));
+        }
+        if !(200..=4000).contains(&self.chart.width) || !(200..=4000).contains(&self.chart.height) {
+            problems.push("chart.width と chart.height は200〜4000で指定してください".to_string());
+        }
+        if self.chart.start_hour >= self.chart.end_hour || self.chart.end_hour > 24 {
+            problems.push(
+                "chart.start_hour は chart.end_hour より前、chart.end_hour は24以下で指定してください"
+                    .to_string(),
+            );
+        }
+        // グラフは1つの投票期間の時刻を並べるため、表示する時間帯の途中で期間が切り替わると前後が入れ替わる
+        if self.chart.start_hour < self.period.rollover_hour
+            && self.period.rollover_hour < self.chart.end_hour
+        {
+            problems.push(format!(
+                "period.rollover_hour（{}時）が chart.start_hour〜chart.end_hour（{}〜{}時）の途中にあります。グラフの時間帯の外か開始時刻にしてください",
+                self.period.rollover_hour, self.chart.start_hour, self.chart.end_hour
+            ));
+        }
+        if let Err(e) = EnvFilter::try_new(&self.log.level) {
+            problems.push(format!(
+                "log.level: 「{}」はログのレベルとして読み取れません: {}",
+                self.log.level, e
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn parse(content: &str, env: &[(&str, &str)]) -> Result<Config, Vec<String>> {
+        let env: HashMap<String, String> = env
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        Config::parse(Some(content), |name| env.get(name).cloned())
+    }
+
+    #[test]
+    fn example_file_matches_defaults() {
+        let defaults = serde_json::to_value(Config::default()).unwrap();
+
+        let empty = Config::parse(None, |_| None).unwrap();
+        assert_eq!(serde_json::to_value(empty).unwrap(), defaults);
+
+        let example = parse(include_str!("../config.example.toml"), &[]).unwrap();
+        assert_eq!(serde_json::to_value(example).unwrap(), defaults);
+    }
+
+    #[test]
+    fn unknown_sections_and_keys_are_reported() {
+        let problems = parse("[chart]\ncolour = \"red\"\n\n[colors]\nfound = 1\n", &[])
+            .expect_err("不明な項目を受け付けた");
+
+        assert_eq!(
+            problems,
+            vec![
+                "不明な設定項目です: chart.colour".to_string(),
+                "不明なセクションです: [colors]".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn every_bad_value_is_reported_by_name() {
+        let problems = parse(
+            "[period]\ntimezone = \"Mars/Olympus\"\n\n\
+             [vote]\nchange_cooldown_secs = -1\n\n\
+             [chart]\nstyle = \"pie\"\nwidth = \"wide\"\n\n\
+             [http]\nlisten = \"localhost\"\n",
+            &[],
+        )
+        .expect_err("正しくない値を受け付けた");
+
+        let names: Vec<&str> = problems
+            .iter()
+            .map(|problem| problem.split(' ').next().unwrap())
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                "chart.style",
+                "chart.width",
+                "http.listen",
+                "period.timezone",
+                "vote.change_cooldown_secs"
+            ]
+        );
+        assert!(problems[3].contains("Mars/Olympus"), "{}", problems[3]);
+    }
+
+    #[test]
+    fn env_overrides_file_values() {
+        let config = parse(
+            "[chart]\nstyle = \"line\"\n\n[features]\nexport = true\n",
+            &[
+                ("KEBAB_CHART_STYLE", "bar"),
+                ("KEBAB_FEATURES_EXPORT", "off"),
+                ("KEBAB_BOARD_EDIT_INTERVAL_MS", " 250 "),
+                ("KEBAB_PERIOD_TIMEZONE", "Asia/Seoul"),
+                ("DISCORD_TOKEN", "12345"),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(config.chart.style, ChartStyle::Bar);
+        assert!(!config.features.export);
+        assert_eq!(config.board.edit_interval_ms, 250);
+        assert_eq!(config.period.timezone, chrono_tz::Asia::Seoul);
+        assert_eq!(config.discord.token.as_deref(), Some("12345"));
+
+        let problems = parse("", &[("KEBAB_BOARD_EDIT_INTERVAL_MS", "fast")])
+            .expect_err("整数でない環境変数を受け付けた");
+        assert!(
+            problems[0].starts_with("board.edit_interval_ms "),
+            "{problems:?}"
+        );
+
+        let config = parse("", &[("DISCORD_TOKEN", "")]).unwrap();
+        assert_eq!(config.discord.token, None);
+    }
+
+    #[test]
+    fn rollover_inside_chart_hours_is_rejected() {
+        let problems = parse("[period]\nrollover_hour = 16\n", &[])
+            .expect_err("グラフの途中で期間が切り替わる設定を受け付けた");
+        assert!(
+            problems[0].starts_with("period.rollover_hour"),
+            "{problems:?}"
+        );
+
+        assert!(parse("[period]\nrollover_hour = 14\n", &[]).is_ok());
+        assert!(parse("[period]\nrollover_hour = 3\n", &[]).is_ok());
+    }
+}
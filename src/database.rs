@@ -0,0 +1,38 @@
+use migration::{Migrator, MigratorTrait};
+use sea_orm::{Database, DatabaseConnection, DbErr};
+
+/// 設定されていない場合に使用するデータベース
+pub const DEFAULT_DATABASE_URL: &str = "sqlite:bot_data.db?mode=rwc";
+
+/// 指定したURLのデータベースに接続し、未実行のマイグレーションを適用する
+pub async fn connect(url: &str) -> Result<DatabaseConnection, DbErr> {
+    check_driver(url)?;
+
+    let database = Database::connect(url).await?;
+    Migrator::up(&database, None).await?;
+    Ok(database)
+}
+
+/// URLのデータベースのドライバーがビルドに含まれているか確認する
+fn check_driver(url: &str) -> Result<(), DbErr> {
+    let (scheme, feature, enabled) =
+        if url.starts_with("postgres://") || url.starts_with("postgresql://") {
+            ("PostgreSQL", "postgres", cfg!(feature = "postgres"))
+        } else if url.starts_with("sqlite:") {
+            ("SQLite", "sqlite", cfg!(feature = "sqlite"))
+        } else {
+            return Err(DbErr::Custom(format!(
+                "DATABASE_URL は sqlite: または postgres:// で始まる必要があります: {}",
+                url
+            )));
+        };
+
+    if !enabled {
+        return Err(DbErr::Custom(format!(
+            "{}を使用するには `--features {}` を付けてビルドしてください",
+            scheme, feature
+        )));
+    }
+
+    Ok(())
+}
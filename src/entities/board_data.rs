@@ -10,6 +10,7 @@ pub struct Model {
     pub server_id: i64,
     pub channel_id: i64,
     pub message_id: i64,
+    pub chart_style: String,
     pub created_at: DateTimeWithTimeZone,
     pub updated_at: DateTimeWithTimeZone,
 }
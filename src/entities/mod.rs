@@ -3,4 +3,9 @@
 pub mod prelude;
 
 pub mod board_data;
+pub mod guild_settings;
+pub mod user_reputation;
 pub mod vote;
+pub mod vote_history;
+pub mod vote_report;
+pub mod voter_ban;
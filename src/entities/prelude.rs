@@ -1,4 +1,9 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.12
 
 pub use super::board_data::Entity as BoardData;
+pub use super::guild_settings::Entity as GuildSettings;
+pub use super::user_reputation::Entity as UserReputation;
 pub use super::vote::Entity as Vote;
+pub use super::vote_history::Entity as VoteHistory;
+pub use super::vote_report::Entity as VoteReport;
+pub use super::voter_ban::Entity as VoterBan;
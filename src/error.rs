@@ -0,0 +1,173 @@
+use crate::Data;
+use crate::config::ConfigError;
+use crate::metrics::METRICS;
+use poise::CreateReply;
+use poise::serenity_prelude as serenity;
+use sea_orm::DbErr;
+use tracing::{debug, error, warn};
+
+/// Bot・管理ツール全体で使うエラー
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("データベースのエラー: {0}")]
+    Database(#[from] DbErr),
+    #[error("Discordのエラー: {0}")]
+    Discord(#[from] serenity::Error),
+    #[error("グラフの生成に失敗しました: {0}")]
+    Chart(#[source] Box<dyn std::error::Error + Send + Sync>),
+    #[error("{0}")]
+    Config(#[from] ConfigError),
+    /// ユーザーまたはBotに必要な権限がない
+    #[error("{0}")]
+    Permission(String),
+    /// サーバー内でのみ使用できる操作をDMなどで実行した
+    #[error("サーバー内でのみ使用できます")]
+    NotInGuild,
+    /// ユーザーが指定した値やファイルの内容が正しくない（メッセージはそのまま表示する）
+    #[error("{0}")]
+    InvalidInput(String),
+    #[error("入出力のエラー: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("JSONのエラー: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+impl Error {
+    /// コマンドを実行したユーザーに表示するメッセージ
+    pub fn user_message(&self) -> String {
+        match self {
+            Error::Database(_) => {
+                "❌ データベースの処理に失敗しました。しばらくしてからもう一度お試しください。"
+                    .to_string()
+            }
+            Error::Discord(_) => {
+                "❌ Discordとの通信に失敗しました。Botの権限を確認してもう一度お試しください。"
+                    .to_string()
+            }
+            Error::Chart(_) => "❌ グラフの生成に失敗しました。".to_string(),
+            Error::Config(_) => {
+                "❌ Botの設定に問題があります。サーバーの管理者に連絡してください。".to_string()
+            }
+            Error::Permission(message) => format!("🚫 {}", message),
+            Error::NotInGuild => "❌ このコマンドはサーバー内でのみ使用できます。".to_string(),
+            Error::InvalidInput(message) => format!("❌ {}", message),
+            Error::Io(_) | Error::Json(_) => "❌ 処理中にエラーが発生しました。".to_string(),
+        }
+    }
+
+    /// 起動に失敗した場合に表示する対処方法
+    pub fn hint(&self) -> Option<&'static str> {
+        match self {
+            Error::Database(_) => Some(
+                "DATABASE_URL（または config.toml の [database] url）の接続先とデータベースの状態を確認してください",
+            ),
+            Error::Discord(serenity::Error::Gateway(
+                serenity::GatewayError::InvalidAuthentication,
+            ))
+            | Error::Discord(serenity::Error::Http(serenity::HttpError::UnsuccessfulRequest(_))) => {
+                Some(
+                    "DISCORD_TOKEN が正しいか、Discord Developer Portal でBotのトークンを確認してください",
+                )
+            }
+            Error::Discord(serenity::Error::Gateway(
+                serenity::GatewayError::DisallowedGatewayIntents,
+            )) => Some("Discord Developer Portal でBotに必要なIntentを有効にしてください"),
+            Error::Discord(_) => Some("ネットワークの接続とDiscordの稼働状況を確認してください"),
+            Error::Config(_) => Some("config.example.toml を参考に設定を修正してください"),
+            Error::Io(_) => Some("ファイルのパスと権限を確認してください"),
+            _ => None,
+        }
+    }
+
+    /// 起動に失敗した場合の終了コード（sysexits.h に合わせる）
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            // EX_CONFIG
+            Error::Config(_) => 78,
+            // EX_UNAVAILABLE
+            Error::Database(_) | Error::Discord(_) => 69,
+            // EX_IOERR
+            Error::Io(_) => 74,
+            // EX_SOFTWARE
+            _ => 70,
+        }
+    }
+}
+
+/// コマンドの実行中に発生したエラーをユーザーに伝える
+pub async fn on_error(error: poise::FrameworkError<'_, Data, Error>) {
+    match error {
+        poise::FrameworkError::Command { error, ctx, .. } => {
+            match &error {
+                Error::Permission(_) | Error::NotInGuild | Error::InvalidInput(_) => {
+                    debug!(command = %ctx.command().qualified_name, error = %error, "コマンドを実行できませんでした");
+                }
+                Error::Discord(_) => {
+                    METRICS.record_discord_api_error("command");
+                    error!(command = %ctx.command().qualified_name, error = %error, "コマンドの実行中にエラーが発生しました");
+                }
+                _ => {
+                    error!(command = %ctx.command().qualified_name, error = %error, "コマンドの実行中にエラーが発生しました");
+                }
+            }
+            reply_error(ctx, &error).await;
+        }
+        poise::FrameworkError::GuildOnly { ctx, .. } => {
+            reply_error(ctx, &Error::NotInGuild).await;
+        }
+        poise::FrameworkError::MissingBotPermissions {
+            missing_permissions,
+            ctx,
+            ..
+        } => {
+            let error =
+                Error::Permission(format!("Botに次の権限が必要です: {}", missing_permissions));
+            reply_error(ctx, &error).await;
+        }
+        poise::FrameworkError::MissingUserPermissions {
+            missing_permissions,
+            ctx,
+            ..
+        } => {
+            let message = match missing_permissions {
+                Some(permissions) => {
+                    format!(
+                        "このコマンドを実行するには次の権限が必要です: {}",
+                        permissions
+                    )
+                }
+                None => "このコマンドを実行する権限がありません。".to_string(),
+            };
+            reply_error(ctx, &Error::Permission(message)).await;
+        }
+        // チェック側で理由を返答済み（終了処理中など）
+        poise::FrameworkError::CommandCheckFailed {
+            error: None, ctx, ..
+        } => {
+            debug!(command = %ctx.command().qualified_name, "コマンドのチェックにより実行しませんでした");
+        }
+        poise::FrameworkError::CommandCheckFailed {
+            error: Some(error),
+            ctx,
+            ..
+        } => {
+            reply_error(ctx, &error).await;
+        }
+        error => {
+            if let Err(e) = poise::builtins::on_error(error).await {
+                warn!(error = %e, "エラーの処理中にエラーが発生しました");
+            }
+        }
+    }
+}
+
+/// エラーの内容をコマンドを実行したユーザーだけに表示する
+async fn reply_error(ctx: crate::Context<'_>, error: &Error) {
+    let rep = CreateReply::default()
+        .content(error.user_message())
+        .ephemeral(true);
+    if let Err(e) = ctx.send(rep).await {
+        METRICS.record_discord_api_error("error_reply");
+        warn!(error = %e, "エラーメッセージの送信に失敗しました");
+    }
+}
@@ -0,0 +1,118 @@
+//! 死活監視と準備状態の確認に使うBotの状態
+
+use chrono::{DateTime, Utc};
+use sea_orm::DatabaseConnection;
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+use std::time::Duration;
+
+/// データベースの応答を待つ時間
+const DATABASE_PING_TIMEOUT: Duration = Duration::from_secs(2);
+/// 定期チェックが止まっていると判断するまでの猶予（チェック間隔の2倍に加える）
+const PERIODIC_CHECK_GRACE: Duration = Duration::from_secs(60);
+
+/// ゲートウェイの接続状態と定期チェックの実行状況
+pub struct HealthState {
+    gateway_connected: AtomicBool,
+    /// 最後に定期チェックが成功した時刻（UNIXミリ秒、未実行なら0）
+    last_periodic_check: AtomicI64,
+    started_at: DateTime<Utc>,
+}
+
+/// `/healthz`・`/readyz`で返す状態
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthReport {
+    /// `ok`または`unavailable`
+    pub status: &'static str,
+    pub database: bool,
+    pub gateway_connected: bool,
+    /// 最後に定期チェックが成功してからの秒数（まだ一度も成功していなければ`None`）
+    pub seconds_since_last_periodic_check: Option<i64>,
+    /// 定期チェックが間隔を大きく超えて止まっている
+    pub periodic_check_stale: bool,
+    /// 終了処理中で新しい操作を受け付けていない
+    pub shutting_down: bool,
+}
+
+impl Default for HealthState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HealthState {
+    pub fn new() -> Self {
+        Self {
+            gateway_connected: AtomicBool::new(false),
+            last_periodic_check: AtomicI64::new(0),
+            started_at: Utc::now(),
+        }
+    }
+
+    /// ゲートウェイの接続状態を更新する
+    pub fn set_gateway_connected(&self, connected: bool) {
+        self.gateway_connected.store(connected, Ordering::Relaxed);
+    }
+
+    pub fn gateway_connected(&self) -> bool {
+        self.gateway_connected.load(Ordering::Relaxed)
+    }
+
+    /// 投票期間の定期チェックが成功したことを記録する
+    pub fn record_periodic_check(&self) {
+        self.last_periodic_check
+            .store(Utc::now().timestamp_millis(), Ordering::Relaxed);
+    }
+
+    /// 最後に定期チェックが成功した時刻
+    pub fn last_periodic_check(&self) -> Option<DateTime<Utc>> {
+        match self.last_periodic_check.load(Ordering::Relaxed) {
+            0 => None,
+            millis => DateTime::from_timestamp_millis(millis),
+        }
+    }
+
+    /// 現在の状態を調べる
+    /// データベースには実際に問い合わせ、応答がなければ到達できないものとして扱う
+    pub async fn report(&self, db: &DatabaseConnection, check_interval: Duration) -> HealthReport {
+        let database = matches!(
+            tokio::time::timeout(DATABASE_PING_TIMEOUT, db.ping()).await,
+            Ok(Ok(()))
+        );
+        let gateway_connected = self.gateway_connected();
+
+        let now = Utc::now();
+        let last_check = self.last_periodic_check();
+        // 一度も成功していなければ起動時刻から数える
+        let since = now - last_check.unwrap_or(self.started_at);
+        let stale_after = check_interval * 2 + PERIODIC_CHECK_GRACE;
+        let periodic_check_stale = since.to_std().is_ok_and(|since| since > stale_after);
+
+        HealthReport {
+            status: "ok",
+            database,
+            gateway_connected,
+            seconds_since_last_periodic_check: last_check.map(|last| (now - last).num_seconds()),
+            periodic_check_stale,
+            shutting_down: false,
+        }
+    }
+}
+
+impl HealthReport {
+    /// プロセスが動き続けているか（定期チェックが止まっていないか）
+    pub fn is_alive(&self) -> bool {
+        !self.periodic_check_stale
+    }
+
+    /// 投票を受け付けられる状態か（終了処理中でなく、データベースとゲートウェイに接続できているか）
+    pub fn is_ready(&self) -> bool {
+        !self.shutting_down && self.database && self.gateway_connected
+    }
+
+    /// `status`を判定結果に合わせる
+    pub fn with_result(mut self, ok: bool) -> Self {
+        self.status = if ok { "ok" } else { "unavailable" };
+        self
+    }
+}
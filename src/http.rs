@@ -0,0 +1,112 @@
+//! 監視用の小さなHTTPサーバー
+
+use crate::Data;
+use crate::health::HealthReport;
+use crate::metrics::METRICS;
+use http_body_util::Full;
+use hyper::body::{Bytes, Incoming};
+use hyper::server::conn::http1;
+use hyper::service::service_fn;
+use hyper::{Method, Request, Response, StatusCode, header};
+use hyper_util::rt::TokioIo;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tracing::{info, warn};
+
+/// 指定したアドレスで監視用のエンドポイントを公開する
+///
+/// - `GET /metrics`: Prometheusのテキスト形式のメトリクス
+/// - `GET /healthz`: 定期チェックが止まっていなければ200（死活監視用）
+/// - `GET /readyz`: 終了処理中でなく、データベースとゲートウェイに接続できていれば200（準備状態の確認用）
+///
+/// `/healthz`と`/readyz`は判定に使った状態をJSONで返し、問題があれば503を返す。
+pub async fn serve(listen: SocketAddr, data: Data) -> std::io::Result<()> {
+    let listener = TcpListener::bind(listen).await?;
+    info!(%listen, "監視用のHTTPサーバーを開始しました");
+
+    loop {
+        let (stream, _) = match listener.accept().await {
+            Ok(connection) => connection,
+            Err(e) => {
+                warn!(error = %e, "HTTP接続の受け付けに失敗しました");
+                continue;
+            }
+        };
+
+        let data = data.clone();
+        tokio::spawn(async move {
+            let service = service_fn(|request| handle(request, &data));
+            if let Err(e) = http1::Builder::new()
+                .serve_connection(TokioIo::new(stream), service)
+                .await
+            {
+                warn!(error = %e, "HTTP接続の処理中にエラーが発生しました");
+            }
+        });
+    }
+}
+
+async fn handle(
+    request: Request<Incoming>,
+    data: &Data,
+) -> Result<Response<Full<Bytes>>, Infallible> {
+    let response = match (request.method(), request.uri().path()) {
+        (&Method::GET, "/metrics") => text_response(
+            StatusCode::OK,
+            "text/plain; version=0.0.4; charset=utf-8",
+            METRICS.render(),
+        ),
+        (&Method::GET, "/healthz") => {
+            let report = health_report(data).await;
+            let alive = report.is_alive();
+            health_response(report.with_result(alive))
+        }
+        (&Method::GET, "/readyz") => {
+            let report = health_report(data).await;
+            let ready = report.is_ready();
+            health_response(report.with_result(ready))
+        }
+        (&Method::GET, _) => text_response(
+            StatusCode::NOT_FOUND,
+            "text/plain; charset=utf-8",
+            "not found\n".to_string(),
+        ),
+        _ => text_response(
+            StatusCode::METHOD_NOT_ALLOWED,
+            "text/plain; charset=utf-8",
+            "method not allowed\n".to_string(),
+        ),
+    };
+    Ok(response)
+}
+
+async fn health_report(data: &Data) -> HealthReport {
+    let check_interval = Duration::from_secs(data.config.board.check_interval_secs);
+    let mut report = data.health.report(&data.database, check_interval).await;
+    report.shutting_down = data.shutdown.is_shutting_down();
+    report
+}
+
+/// 状態をJSONで返す（問題があれば503）
+fn health_response(report: HealthReport) -> Response<Full<Bytes>> {
+    let status = if report.status == "ok" {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    let body = serde_json::to_string(&report).unwrap_or_default();
+    text_response(status, "application/json", body)
+}
+
+fn text_response(status: StatusCode, content_type: &str, body: String) -> Response<Full<Bytes>> {
+    let mut response = Response::new(Full::new(Bytes::from(body)));
+    *response.status_mut() = status;
+    if let Ok(content_type) = content_type.parse() {
+        response
+            .headers_mut()
+            .insert(header::CONTENT_TYPE, content_type);
+    }
+    response
+}
@@ -0,0 +1,36 @@
+use config::Config;
+use health::HealthState;
+use sea_orm::DatabaseConnection;
+use services::{AuditLog, ChartCache, ReputationCache, VoteCooldown};
+use shutdown::Shutdown;
+use std::sync::Arc;
+
+pub mod commands;
+pub mod config;
+pub mod database;
+pub mod entities;
+pub mod error;
+pub mod health;
+pub mod http;
+pub mod logging;
+pub mod metrics;
+pub mod services;
+pub mod shutdown;
+
+pub use error::Error;
+pub type Context<'a> = poise::Context<'a, Data, Error>;
+
+// ユーザーデータ構造体
+// コマンドとイベントハンドラーで共有するため、中身は全てArcで保持する
+#[derive(Clone)]
+pub struct Data {
+    pub database: Arc<DatabaseConnection>,
+    pub chart_cache: Arc<ChartCache>,
+    pub reputation_cache: Arc<ReputationCache>,
+    pub vote_cooldown: Arc<VoteCooldown>,
+    pub report_cooldown: Arc<VoteCooldown>,
+    pub audit_log: Arc<AuditLog>,
+    pub config: Arc<Config>,
+    pub health: Arc<HealthState>,
+    pub shutdown: Arc<Shutdown>,
+}
@@ -0,0 +1,22 @@
+use crate::config::{LogConfig, LogFormat};
+use tracing_subscriber::EnvFilter;
+
+/// 設定に従ってログの出力を初期化する
+///
+/// ログは標準エラー出力に書き出すため、管理ツールの標準出力とは混ざらない。
+pub fn init(config: &LogConfig) {
+    // 設定の読み込み時に検証済みだが、念のため読み取れなければ info にする
+    let filter = EnvFilter::try_new(&config.level).unwrap_or_else(|_| EnvFilter::new("info"));
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(std::io::stderr);
+
+    match config.format {
+        LogFormat::Text => builder.init(),
+        LogFormat::Json => builder
+            .json()
+            .with_current_span(true)
+            .with_span_list(true)
+            .init(),
+    }
+}
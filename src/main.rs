@@ -1,39 +1,95 @@
-use migration::{Migrator, MigratorTrait};
+use chrono::Utc;
+use kebab_bot::config::{Config, ConfigError};
+use kebab_bot::error::on_error;
+use kebab_bot::health::HealthState;
+use kebab_bot::metrics::METRICS;
+use kebab_bot::shutdown::{self, Shutdown};
+use kebab_bot::{Data, Error, commands::*, database, http, logging, services::*};
+use poise::CreateReply;
 use poise::serenity_prelude::{
-    self as serenity, ComponentInteraction, CreateInteractionResponse,
-    CreateInteractionResponseMessage, EventHandler, Interaction, Ready, async_trait,
+    self as serenity, ActionRowComponent, ComponentInteraction, CreateActionRow, CreateInputText,
+    CreateInteractionResponse, CreateInteractionResponseMessage, CreateModal, EventHandler,
+    InputTextStyle, Interaction, ModalInteraction, Ready, ResumedEvent, ShardStageUpdateEvent,
+    async_trait,
 };
-use sea_orm::{Database, DatabaseConnection};
+use std::process::ExitCode;
 use std::sync::Arc;
+use std::time::Instant;
 use tokio::time::{Duration, interval};
-
-mod commands;
-mod entities;
-mod services;
-
-use commands::*;
-use services::*;
-
-type Error = Box<dyn std::error::Error + Send + Sync>;
-type Context<'a> = poise::Context<'a, Data, Error>;
-
-// ユーザーデータ構造体
-pub struct Data {
-    database: Arc<DatabaseConnection>,
+use tokio_util::task::task_tracker::TaskTrackerToken;
+use tracing::{Instrument, error, info, info_span, instrument, warn};
+
+/// 終了処理中に届いた操作への返答
+const SHUTTING_DOWN_MESSAGE: &str =
+    "⏳ Botを再起動しています。しばらくしてからもう一度お試しください。";
+
+/// 報告を入力するモーダルのID
+const REPORT_MODAL_ID: &str = "report_modal";
+/// 報告のモーダルの入力欄のID
+const REPORT_NOTE_INPUT_ID: &str = "note";
+
+/// コマンドの実行中に保持する情報（実行が終わると破棄される）
+struct CommandInvocation {
+    started: Instant,
+    /// 終了処理で完了を待つ対象として数える
+    _in_flight: TaskTrackerToken,
 }
 
 // 投票処理を行う共通関数
+#[instrument(skip(ctx, interaction, data, success_message))]
 async fn handle_vote(
     ctx: &serenity::Context,
     interaction: &ComponentInteraction,
-    database: &Arc<DatabaseConnection>,
+    data: &Data,
     action: &str,
     success_message: &str,
 ) -> Result<(), Error> {
+    let database = &data.database;
     let user_id = interaction.user.id.get() as i64;
+    let guild_id = interaction.guild_id.map(|id| id.get() as i64);
+
+    // サーバーで設定された投票の条件を満たしているか確認する
+    if !check_vote_eligibility(ctx, interaction, data).await? {
+        return Ok(());
+    }
 
-    match VoteService::update_vote(database, user_id, action.to_string()).await {
+    // 連打対策: 前回の投票から待ち時間が過ぎるまで変更を受け付けない
+    let cooldown_key = VoteCooldownKey::vote(user_id);
+    if let Err(remaining) = data.vote_cooldown.try_acquire(cooldown_key) {
+        let remaining_secs = remaining.as_secs_f64().ceil() as u64;
+        METRICS.record_rate_limited_vote(action);
+        info!(remaining_secs, "待ち時間中のため投票を受け付けませんでした");
+
+        let response = CreateInteractionResponseMessage::new()
+            .content(format!("⏳ 投票は{}秒後に変更できます。", remaining_secs))
+            .ephemeral(true);
+        interaction
+            .create_response(&ctx.http, CreateInteractionResponse::Message(response))
+            .await?;
+        return Ok(());
+    }
+
+    // 監査ログ用に変更前の投票を取得しておく
+    let old_action = VoteService::get_vote(database, user_id)
+        .await?
+        .map(|vote| vote.action);
+
+    match VoteService::update_vote(database, user_id, guild_id, action.to_string()).await {
         Ok(_) => {
+            data.chart_cache.invalidate();
+            METRICS.record_vote(action);
+            info!("投票を保存しました");
+            if let Some(guild_id) = guild_id {
+                data.audit_log.record(
+                    guild_id,
+                    AuditEvent::VoteChanged {
+                        user_id,
+                        old_action,
+                        new_action: action.to_string(),
+                    },
+                );
+            }
+
             let response = CreateInteractionResponseMessage::new()
                 .content(success_message)
                 .ephemeral(true);
@@ -43,7 +99,9 @@ async fn handle_vote(
                 .await?;
         }
         Err(e) => {
-            eprintln!("投票の保存中にエラーが発生しました: {}", e);
+            error!(error = %e, "投票の保存中にエラーが発生しました");
+            // 保存できなかった投票では待ち時間を発生させない
+            data.vote_cooldown.release(cooldown_key);
             let response = CreateInteractionResponseMessage::new()
                 .content("投票の保存に失敗しました。")
                 .ephemeral(true);
@@ -54,36 +112,226 @@ async fn handle_vote(
         }
     }
 
-    // 投票データを取得してタイムラインチャートを生成
-    let votes = VoteService::get_all_votes(database).await?;
-    if !votes.is_empty() {
-        let timeline_path = "vote_timeline.png";
-        if let Err(e) = ChartService::generate_vote_timeline_chart(votes, timeline_path).await {
-            eprintln!("タイムラインチャート生成エラー: {}", e);
+    BoardUIService::refresh_all_board_messages_serenity(ctx, data).await
+}
+
+// 投票の取り消しボタンの処理
+#[instrument(skip_all)]
+async fn handle_retract(
+    ctx: &serenity::Context,
+    interaction: &ComponentInteraction,
+    data: &Data,
+) -> Result<(), Error> {
+    let user_id = interaction.user.id.get() as i64;
+    let guild_id = interaction.guild_id.map(|id| id.get() as i64);
+
+    let outcome = VoteService::retract_user_vote(data, user_id, guild_id).await?;
+
+    let response = CreateInteractionResponseMessage::new()
+        .content(outcome.message())
+        .ephemeral(true);
+    interaction
+        .create_response(&ctx.http, CreateInteractionResponse::Message(response))
+        .await?;
+
+    if matches!(outcome, RetractOutcome::Retracted { .. }) {
+        BoardUIService::refresh_all_board_messages_serenity(ctx, data).await?;
+    }
+    Ok(())
+}
+
+// モデレーターに投票を禁止されていないか、サーバーで設定された投票の条件を満たしているか確認する
+// 投票できない場合は理由を返答して`false`を返す
+async fn check_vote_eligibility(
+    ctx: &serenity::Context,
+    interaction: &ComponentInteraction,
+    data: &Data,
+) -> Result<bool, Error> {
+    if let Some(guild_id) = interaction.guild_id {
+        let ban = ModerationService::get_active_ban(
+            &data.database,
+            guild_id.get() as i64,
+            interaction.user.id.get() as i64,
+            Utc::now(),
+        )
+        .await?;
+        if let Some(ban) = ban {
+            METRICS.record_ineligible_vote("banned");
+            info!("投票を禁止されているため受け付けませんでした");
+
+            let response = CreateInteractionResponseMessage::new()
+                .content(ModerationService::ban_message(&ban))
+                .ephemeral(true);
+            interaction
+                .create_response(&ctx.http, CreateInteractionResponse::Message(response))
+                .await?;
+            return Ok(false);
         }
     }
 
-    let board_data = BoardService::get_all_board_data(database).await?;
-    if board_data.is_empty() {
-        // serenity用の関数は直接関数として呼び出すのではなく、BoardUIServiceのメソッドとして使用する
-        // しかし、ここではinteractionを持っていないので、単純にreturnする
+    let Some(member) = &interaction.member else {
+        return Ok(true);
+    };
+
+    let guild_id = interaction.guild_id.map(|id| id.get() as i64);
+    let eligibility = GuildSettingsService::get_vote_eligibility(&data.database, guild_id).await?;
+    let Err(reason) = eligibility.check(&Voter::from_member(member), Utc::now()) else {
+        return Ok(true);
+    };
+
+    METRICS.record_ineligible_vote(reason.code());
+    info!(
+        reason = reason.code(),
+        "投票の条件を満たしていないため受け付けませんでした"
+    );
+
+    let response = CreateInteractionResponseMessage::new()
+        .content(reason.message())
+        .ephemeral(true);
+    interaction
+        .create_response(&ctx.http, CreateInteractionResponse::Message(response))
+        .await?;
+    Ok(false)
+}
+
+// 報告ボタンの処理: 報告を入力するモーダルを開く
+#[instrument(skip_all)]
+async fn handle_report_button(
+    ctx: &serenity::Context,
+    interaction: &ComponentInteraction,
+    data: &Data,
+) -> Result<(), Error> {
+    // 投票と同じ条件を満たすユーザーだけが報告できる
+    if !check_vote_eligibility(ctx, interaction, data).await? {
+        return Ok(());
+    }
+
+    // 入力してから断られないよう、モーダルを開く前に待ち時間を確認する
+    let cooldown_key = VoteCooldownKey {
+        guild_id: interaction.guild_id.map(|id| id.get() as i64),
+        user_id: interaction.user.id.get() as i64,
+    };
+    if let Some(remaining) = data.report_cooldown.remaining(cooldown_key) {
+        let remaining_secs = remaining.as_secs_f64().ceil() as u64;
+        METRICS.record_rate_limited_vote("report");
+        let response = CreateInteractionResponseMessage::new()
+            .content(format!("⏳ 報告は{}秒後にできます。", remaining_secs))
+            .ephemeral(true);
+        interaction
+            .create_response(&ctx.http, CreateInteractionResponse::Message(response))
+            .await?;
         return Ok(());
     }
 
-    let _response =
-        BoardUIService::update_all_board_messages_serenity(&ctx, board_data, database).await?;
+    let note = CreateInputText::new(InputTextStyle::Paragraph, "内容", REPORT_NOTE_INPUT_ID)
+        .placeholder("例: 12時半から営業しています。行列は5人くらい")
+        .max_length(MAX_REPORT_CHARS as u16)
+        .required(true);
+    let modal = CreateModal::new(REPORT_MODAL_ID, "ケバブ屋の様子を報告")
+        .components(vec![CreateActionRow::InputText(note)]);
+    interaction
+        .create_response(&ctx.http, CreateInteractionResponse::Modal(modal))
+        .await?;
     Ok(())
 }
 
+// 報告のモーダルが送信された時の処理
+#[instrument(
+    skip_all,
+    fields(
+        guild_id = interaction.guild_id.map(|id| id.get()),
+        user_id = interaction.user.id.get(),
+    )
+)]
+async fn handle_report_submit(
+    ctx: &serenity::Context,
+    interaction: &ModalInteraction,
+    data: &Data,
+) -> Result<(), Error> {
+    let user_id = interaction.user.id.get() as i64;
+    let guild_id = interaction.guild_id.map(|id| id.get() as i64);
+
+    let note = interaction
+        .data
+        .components
+        .iter()
+        .flat_map(|row| &row.components)
+        .find_map(|component| match component {
+            ActionRowComponent::InputText(input) if input.custom_id == REPORT_NOTE_INPUT_ID => {
+                input.value.as_deref()
+            }
+            _ => None,
+        })
+        .and_then(ReportService::normalize_note);
+    let Some(note) = note else {
+        let response = CreateInteractionResponseMessage::new()
+            .content("❌ 報告の内容を入力してください。")
+            .ephemeral(true);
+        interaction
+            .create_response(&ctx.http, CreateInteractionResponse::Message(response))
+            .await?;
+        return Ok(());
+    };
+
+    let cooldown_key = VoteCooldownKey { guild_id, user_id };
+    if let Err(remaining) = data.report_cooldown.try_acquire(cooldown_key) {
+        let remaining_secs = remaining.as_secs_f64().ceil() as u64;
+        METRICS.record_rate_limited_vote("report");
+        let response = CreateInteractionResponseMessage::new()
+            .content(format!("⏳ 報告は{}秒後にできます。", remaining_secs))
+            .ephemeral(true);
+        interaction
+            .create_response(&ctx.http, CreateInteractionResponse::Message(response))
+            .await?;
+        return Ok(());
+    }
+
+    match ReportService::create_report(&data.database, user_id, guild_id, note, None).await {
+        Ok(report) => {
+            info!(report_id = report.id, "報告を保存しました");
+            let response = CreateInteractionResponseMessage::new()
+                .content("📝 報告を受け付けました。掲示板に表示されます。")
+                .ephemeral(true);
+            interaction
+                .create_response(&ctx.http, CreateInteractionResponse::Message(response))
+                .await?;
+        }
+        Err(e) => {
+            error!(error = %e, "報告の保存中にエラーが発生しました");
+            // 保存できなかった報告では待ち時間を発生させない
+            data.report_cooldown.release(cooldown_key);
+            let response = CreateInteractionResponseMessage::new()
+                .content("報告の保存に失敗しました。")
+                .ephemeral(true);
+            interaction
+                .create_response(&ctx.http, CreateInteractionResponse::Message(response))
+                .await?;
+            return Ok(());
+        }
+    }
+
+    BoardUIService::refresh_all_board_messages_serenity(ctx, data).await
+}
+
 // ボタンインタラクションを処理する関数
+#[instrument(
+    skip_all,
+    fields(
+        guild_id = interaction.guild_id.map(|id| id.get()),
+        user_id = interaction.user.id.get(),
+        custom_id = %interaction.data.custom_id,
+    )
+)]
 async fn handle_button_interaction(
     ctx: &serenity::Context,
     interaction: &ComponentInteraction,
-    database: &Arc<DatabaseConnection>,
+    data: &Data,
 ) -> Result<(), Error> {
+    let database = &data.database;
+
     // まず日付チェックを行い、必要に応じて投票をリセットして掲示板を更新
-    if let Err(e) = VoteService::check_reset_and_update_board_if_new_day(database, ctx).await {
-        eprintln!("日付チェック中にエラーが発生しました: {}", e);
+    if let Err(e) = VoteService::check_reset_and_update_board_if_new_day(data, ctx).await {
+        error!(error = %e, "日付チェック中にエラーが発生しました");
     }
 
     match interaction.data.custom_id.as_str() {
@@ -116,7 +364,7 @@ async fn handle_button_interaction(
             handle_vote(
                 ctx,
                 interaction,
-                database,
+                data,
                 "found",
                 "🥙 「営業してる」に投票しました！",
             )
@@ -127,7 +375,7 @@ async fn handle_button_interaction(
             handle_vote(
                 ctx,
                 interaction,
-                database,
+                data,
                 "not_found",
                 "❌ 「いない」に投票しました！",
             )
@@ -138,14 +386,36 @@ async fn handle_button_interaction(
             handle_vote(
                 ctx,
                 interaction,
-                database,
+                data,
                 "sold_out",
                 "🚫 「売り切れた」に投票しました！",
             )
             .await?;
         }
+        "report" => {
+            // ケバブ屋の様子を報告するボタン
+            handle_report_button(ctx, interaction, data).await?;
+        }
+        "retract" => {
+            // 投票を取り消すボタン
+            handle_retract(ctx, interaction, data).await?;
+        }
+        "my_vote" => {
+            // 自分の投票を確認するボタン
+            let embed =
+                BoardUIService::create_my_vote_embed(database, interaction.user.id.get() as i64)
+                    .await?;
+            let response = CreateInteractionResponseMessage::new()
+                .embed(embed)
+                .ephemeral(true);
+
+            interaction
+                .create_response(&ctx.http, CreateInteractionResponse::Message(response))
+                .await?;
+        }
         _ => {
             // 未知のボタンID
+            warn!("不明なボタンが押されました");
             let response = CreateInteractionResponseMessage::new()
                 .content("不明なボタンです。")
                 .ephemeral(true);
@@ -159,158 +429,393 @@ async fn handle_button_interaction(
 }
 
 // 定期的に投票期間チェックを行うバックグラウンドタスク（掲示板更新付き）
-async fn periodic_date_check_with_board_update(
-    database: Arc<DatabaseConnection>,
-    serenity_ctx: serenity::Context,
-) {
-    // 毎時0分に実行するため、現在時刻から次の0分までの時間を計算
-    let mut interval = interval(Duration::from_secs(3600)); // 1時間ごと
+async fn periodic_date_check_with_board_update(data: Data, serenity_ctx: serenity::Context) {
+    // 設定された間隔（既定は1時間）ごとに実行する
+    let mut interval = interval(Duration::from_secs(data.config.board.check_interval_secs));
 
     loop {
-        interval.tick().await;
+        // 終了処理が始まったらタスクを終える
+        tokio::select! {
+            _ = interval.tick() => {}
+            _ = data.shutdown.cancelled() => break,
+        }
 
-        match VoteService::check_reset_and_update_board_if_new_day(&database, &serenity_ctx).await {
+        let span = info_span!("periodic_date_check");
+        let check = VoteService::check_reset_and_update_board_if_new_day(&data, &serenity_ctx)
+            .instrument(span.clone());
+        match data.shutdown.track(check).await {
             Ok(reset) => {
+                data.health.record_periodic_check();
+                let _enter = span.enter();
                 if reset {
-                    println!(
-                        "🔄 定期チェック: 投票期間変更による投票リセットと掲示板更新が完了しました！"
-                    );
+                    info!("定期チェック: 投票期間変更による投票リセットと掲示板更新が完了しました");
                 } else {
-                    println!("ℹ️ 定期チェック: 投票期間は継続中です");
+                    info!("定期チェック: 投票期間は継続中です");
                 }
             }
             Err(e) => {
-                eprintln!("⚠️ 定期投票期間チェック中にエラーが発生しました: {}", e);
+                let _enter = span.enter();
+                error!(error = %e, "定期投票期間チェック中にエラーが発生しました");
             }
         }
     }
 }
 
+// 溜まった監査ログを投稿する
+async fn flush_audit_log(data: &Data, http: &serenity::Http) {
+    let send_interval = Duration::from_millis(data.config.board.edit_interval_ms);
+    data.audit_log
+        .flush(http, &data.database, send_interval)
+        .await;
+}
+
+// 設定された間隔ごとに監査ログを投稿するバックグラウンドタスク
+// 終了処理では処理中の作業が終わった後に`main`が最後の投稿を行うため、ここでは投稿せずに終える
+async fn flush_audit_log_periodically(data: Data, http: Arc<serenity::Http>) {
+    let mut interval = interval(Duration::from_secs(data.config.audit.flush_interval_secs));
+
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {}
+            _ = data.shutdown.cancelled() => break,
+        }
+        flush_audit_log(&data, &http).await;
+    }
+}
+
 // イベントハンドラー構造体
 struct Handler {
-    database: Arc<DatabaseConnection>,
+    data: Data,
 }
 
 #[async_trait]
 impl EventHandler for Handler {
     async fn ready(&self, ctx: serenity::Context, ready: Ready) {
-        println!("🤖 {} がログインしました！", ready.user.name);
+        info!(user = %ready.user.name, "ログインしました");
+        self.data.health.set_gateway_connected(true);
 
-        let database_clone = Arc::clone(&self.database);
+        let data_clone = self.data.clone();
         let ctx_clone = ctx.clone();
 
         // 投票期間が変わっていたら投票をリセット
-        match VoteService::check_reset_and_update_board_if_new_day(&database_clone, &ctx_clone)
-            .await
-        {
+        match VoteService::check_reset_and_update_board_if_new_day(&data_clone, &ctx_clone).await {
             Ok(reset) => {
+                data_clone.health.record_periodic_check();
                 if reset {
-                    println!("✅ 投票期間変更による投票リセットが完了しました！");
+                    info!("投票期間変更による投票リセットが完了しました");
                 } else {
-                    println!("ℹ️ 現在の投票期間（午後期間）は継続中です");
+                    info!("現在の投票期間（午後期間）は継続中です");
                 }
             }
             Err(e) => {
-                eprintln!("⚠️ 投票期間チェック中にエラーが発生しました: {}", e);
+                error!(error = %e, "投票期間チェック中にエラーが発生しました");
             }
         }
 
-        tokio::spawn(periodic_date_check_with_board_update(
-            database_clone,
-            ctx_clone,
-        ));
-        println!("🕒 定期日付チェック・掲示板更新タスクを開始しました（1時間ごと）");
+        tokio::spawn(periodic_date_check_with_board_update(data_clone, ctx_clone));
+        info!(
+            interval_secs = self.data.config.board.check_interval_secs,
+            "定期日付チェック・掲示板更新タスクを開始しました"
+        );
+    }
+
+    async fn resume(&self, _ctx: serenity::Context, _event: ResumedEvent) {
+        info!("ゲートウェイに再接続しました");
+        self.data.health.set_gateway_connected(true);
+    }
+
+    async fn shard_stage_update(&self, _ctx: serenity::Context, event: ShardStageUpdateEvent) {
+        let connected = event.new == serenity::ConnectionStage::Connected;
+        if !connected {
+            warn!(shard_id = event.shard_id.0, stage = %event.new, "ゲートウェイとの接続が切れています");
+        }
+        self.data.health.set_gateway_connected(connected);
     }
 
     async fn interaction_create(&self, ctx: serenity::Context, interaction: Interaction) {
-        if let Interaction::Component(component_interaction) = interaction {
-            if let Err(e) =
-                handle_button_interaction(&ctx, &component_interaction, &self.database).await
-            {
-                eprintln!(
-                    "ボタンインタラクションの処理中にエラーが発生しました: {}",
-                    e
+        match interaction {
+            Interaction::Component(component_interaction) => {
+                // 終了処理中は投票を受け付けない
+                if self.data.shutdown.is_shutting_down() {
+                    let response = CreateInteractionResponseMessage::new()
+                        .content(SHUTTING_DOWN_MESSAGE)
+                        .ephemeral(true);
+                    if let Err(e) = component_interaction
+                        .create_response(&ctx.http, CreateInteractionResponse::Message(response))
+                        .await
+                    {
+                        warn!(error = %e, "終了処理中の返答に失敗しました");
+                    }
+                    return;
+                }
+
+                let started = Instant::now();
+                let result = self
+                    .data
+                    .shutdown
+                    .track(handle_button_interaction(
+                        &ctx,
+                        &component_interaction,
+                        &self.data,
+                    ))
+                    .await;
+                METRICS.observe_interaction(
+                    "button",
+                    &component_interaction.data.custom_id,
+                    started.elapsed(),
                 );
+
+                if let Err(e) = result {
+                    if matches!(e, Error::Discord(_)) {
+                        METRICS.record_discord_api_error("interaction");
+                    }
+                    error!(error = %e, "ボタンインタラクションの処理中にエラーが発生しました");
+                }
+            }
+            Interaction::Modal(modal_interaction)
+                if modal_interaction.data.custom_id == REPORT_MODAL_ID =>
+            {
+                // 終了処理中は報告を受け付けない
+                if self.data.shutdown.is_shutting_down() {
+                    let response = CreateInteractionResponseMessage::new()
+                        .content(SHUTTING_DOWN_MESSAGE)
+                        .ephemeral(true);
+                    if let Err(e) = modal_interaction
+                        .create_response(&ctx.http, CreateInteractionResponse::Message(response))
+                        .await
+                    {
+                        warn!(error = %e, "終了処理中の返答に失敗しました");
+                    }
+                    return;
+                }
+
+                let started = Instant::now();
+                let result = self
+                    .data
+                    .shutdown
+                    .track(handle_report_submit(&ctx, &modal_interaction, &self.data))
+                    .await;
+                METRICS.observe_interaction("modal", REPORT_MODAL_ID, started.elapsed());
+
+                if let Err(e) = result {
+                    if matches!(e, Error::Discord(_)) {
+                        METRICS.record_discord_api_error("interaction");
+                    }
+                    error!(error = %e, "報告の処理中にエラーが発生しました");
+                }
             }
+            _ => {}
         }
     }
 }
 
 #[tokio::main]
-async fn main() {
+async fn main() -> ExitCode {
+    match run().await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            // ログの設定を読み込む前に失敗することもあるため標準エラー出力に直接書く
+            eprintln!("起動に失敗しました: {}", e);
+            if let Some(hint) = e.hint() {
+                eprintln!("{}", hint);
+            }
+            ExitCode::from(e.exit_code())
+        }
+    }
+}
+
+async fn run() -> Result<(), Error> {
     // 環境変数を読み込み
     dotenvy::dotenv().ok();
 
-    // データベース接続
-    let database = Database::connect("sqlite:bot_data.db?mode=rwc")
-        .await
-        .expect("データベースに接続できませんでした");
-
-    // マイグレーションを実行
-    Migrator::up(&database, None)
-        .await
-        .expect("マイグレーションの実行に失敗しました");
-
-    println!("データベースの初期化が完了しました！");
-
-    println!("Botを起動しています...");
-    println!(
-        "DISCORD_TOKEN環境変数: {}",
-        if std::env::var("DISCORD_TOKEN").is_ok() {
-            "設定済み"
-        } else {
-            "未設定"
+    // 設定ファイルと環境変数から設定を読み込む
+    let config = Config::load()?;
+    logging::init(&config.log);
+    VoteService::configure_period(config.period);
+    ChartService::configure(&config.chart);
+
+    // データベースに接続してマイグレーションを実行
+    let database = database::connect(&config.database.url).await?;
+
+    info!("データベースの初期化が完了しました");
+
+    info!("Botを起動しています");
+    let Some(token) = config.discord.token.clone() else {
+        return Err(ConfigError {
+            source: "環境変数".to_string(),
+            problems: vec![
+                "Discordのトークンが設定されていません（DISCORD_TOKEN または [discord] token）"
+                    .to_string(),
+            ],
         }
-    );
+        .into());
+    };
+
+    // 設定で有効になっている機能のコマンドのみ登録する
+    let mut commands = vec![
+        help(),
+        ping(),
+        create_board(),
+        update_board(),
+        reset_votes(),
+        vote(),
+        vote_results(),
+        vote_chart(),
+        chart_theme(),
+        board_chart_style(),
+        vote_eligibility(),
+        audit_log_channel(),
+        report(),
+        reputation(),
+        moderation(),
+        stats(),
+        leaderboard(),
+    ];
+    if config.features.export {
+        commands.push(export());
+    }
+    if config.features.import {
+        commands.push(import_history());
+    }
 
-    // データベースをArcで包む
-    let database = Arc::new(database);
-    let database_for_setup = Arc::clone(&database);
-    let database_for_handler = Arc::clone(&database);
+    // 共有データを作成（データベースはArcで包む）
+    let data = Data {
+        database: Arc::new(database),
+        chart_cache: Arc::new(ChartCache::new()),
+        reputation_cache: Arc::new(ReputationCache::new()),
+        vote_cooldown: Arc::new(VoteCooldown::new(Duration::from_secs(
+            config.vote.change_cooldown_secs,
+        ))),
+        report_cooldown: Arc::new(VoteCooldown::new(Duration::from_secs(
+            config.vote.report_cooldown_secs,
+        ))),
+        audit_log: Arc::new(AuditLog::new()),
+        config: Arc::new(config),
+        health: Arc::new(HealthState::new()),
+        shutdown: Arc::new(Shutdown::new()),
+    };
+
+    // 監視用のHTTPサーバーを起動する
+    if data.config.http.enabled {
+        let listen = data.config.http.listen;
+        let data = data.clone();
+        tokio::spawn(async move {
+            if let Err(e) = http::serve(listen, data).await {
+                error!(%listen, error = %e, "監視用のHTTPサーバーを起動できませんでした");
+            }
+        });
+    }
+    let data_for_setup = data.clone();
 
     let framework = poise::Framework::builder()
         .options(poise::FrameworkOptions {
-            commands: vec![
-                help(),
-                ping(),
-                create_board(),
-                update_board(),
-                reset_votes(),
-                vote_results(),
-                vote_chart(),
-            ],
+            commands,
+            pre_command: |ctx| {
+                Box::pin(async move {
+                    info!(
+                        command = %ctx.command().qualified_name,
+                        guild_id = ctx.guild_id().map(|id| id.get()),
+                        user_id = ctx.author().id.get(),
+                        "コマンドを実行します"
+                    );
+                    ctx.set_invocation_data(CommandInvocation {
+                        started: Instant::now(),
+                        _in_flight: ctx.data().shutdown.in_flight(),
+                    })
+                    .await;
+                })
+            },
+            post_command: |ctx| {
+                Box::pin(async move {
+                    let started = ctx
+                        .invocation_data::<CommandInvocation>()
+                        .await
+                        .map(|invocation| invocation.started);
+                    if let Some(started) = started {
+                        METRICS.observe_interaction(
+                            "command",
+                            &ctx.command().qualified_name,
+                            started.elapsed(),
+                        );
+                    }
+                })
+            },
+            // 終了処理中はコマンドを受け付けない
+            command_check: Some(|ctx| {
+                Box::pin(async move {
+                    if !ctx.data().shutdown.is_shutting_down() {
+                        return Ok(true);
+                    }
+                    let rep = ctx
+                        .reply_builder(CreateReply::default())
+                        .content(SHUTTING_DOWN_MESSAGE)
+                        .ephemeral(true);
+                    ctx.send(rep).await?;
+                    Ok(false)
+                })
+            }),
+            on_error: |error| Box::pin(on_error(error)),
             ..Default::default()
         })
         .setup(move |ctx, _ready, framework| {
             Box::pin(async move {
-                println!("登録するコマンド数: {}", framework.options().commands.len());
-                for command in &framework.options().commands {
-                    println!("コマンド名: {}", command.name);
-                }
+                let names: Vec<&str> = framework
+                    .options()
+                    .commands
+                    .iter()
+                    .map(|command| command.name.as_str())
+                    .collect();
+                info!(count = names.len(), commands = ?names, "スラッシュコマンドを登録します");
 
                 match poise::builtins::register_globally(ctx, &framework.options().commands).await {
-                    Ok(_) => println!("✅ スラッシュコマンドの登録が完了しました！"),
-                    Err(e) => eprintln!("❌ スラッシュコマンドの登録に失敗しました: {}", e),
+                    Ok(_) => info!("スラッシュコマンドの登録が完了しました"),
+                    Err(e) => error!(error = %e, "スラッシュコマンドの登録に失敗しました"),
                 }
 
-                Ok(Data {
-                    database: database_for_setup,
-                })
+                Ok(data_for_setup)
             })
         })
         .build();
 
-    let token = std::env::var("DISCORD_TOKEN").expect("missing DISCORD_TOKEN");
     let intents = serenity::GatewayIntents::non_privileged();
 
     // イベントハンドラーを作成
-    let handler = Handler {
-        database: database_for_handler,
-    };
+    let handler = Handler { data: data.clone() };
 
-    let client = serenity::ClientBuilder::new(token, intents)
+    let mut client = serenity::ClientBuilder::new(token, intents)
         .framework(framework)
         .event_handler(handler)
-        .await;
-    client.unwrap().start().await.unwrap();
+        .await?;
+
+    // 溜まった監査ログを定期的に投稿する
+    tokio::spawn(flush_audit_log_periodically(
+        data.clone(),
+        client.http.clone(),
+    ));
+
+    // 終了シグナルを受け取ったら、新しい操作の受け付けを止めて処理中の作業を待ってから切断する
+    // シャードが接続前だと`shutdown_all`では`start`が戻らないため、シグナル側が先に終わればそのまま抜ける
+    let shard_manager = client.shard_manager.clone();
+    let http = client.http.clone();
+    tokio::select! {
+        result = client.start() => result?,
+        signal = shutdown::wait_for_signal() => {
+            info!(signal, "終了シグナルを受け取りました。新しい操作の受け付けを停止します");
+
+            let timeout = Duration::from_secs(data.config.shutdown.timeout_secs);
+            if data.shutdown.drain(timeout).await {
+                info!("処理中の作業が完了しました");
+            }
+            // 処理中だった操作の記録も含めて、残っている監査ログを投稿してから切断する
+            flush_audit_log(&data, &http).await;
+            shard_manager.shutdown_all().await;
+        }
+    }
+
+    // 全てのシャードが停止したらデータベースを閉じる
+    if let Err(e) = data.database.close_by_ref().await {
+        warn!(error = %e, "データベースを閉じる際にエラーが発生しました");
+    }
+    info!("Botを停止しました");
+    Ok(())
 }
@@ -0,0 +1,283 @@
+//! Prometheusのテキスト形式で公開する監視用のメトリクス
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{LazyLock, Mutex};
+use std::time::Duration;
+
+/// プロセス全体で共有するメトリクス
+pub static METRICS: LazyLock<Metrics> = LazyLock::new(Metrics::new);
+
+/// 処理時間のヒストグラムのバケット（秒）
+const DURATION_BUCKETS: [f64; 11] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// 掲示板の編集結果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EditResult {
+    Success,
+    Failure,
+}
+
+impl EditResult {
+    fn code(&self) -> &'static str {
+        match self {
+            EditResult::Success => "success",
+            EditResult::Failure => "failure",
+        }
+    }
+}
+
+/// Botの動作状況のメトリクス
+pub struct Metrics {
+    votes: CounterVec,
+    rate_limited_votes: CounterVec,
+    ineligible_votes: CounterVec,
+    interaction_duration: HistogramVec,
+    board_edits: CounterVec,
+    chart_render_duration: HistogramVec,
+    rollovers: AtomicU64,
+    discord_api_errors: CounterVec,
+}
+
+impl Metrics {
+    fn new() -> Self {
+        Self {
+            votes: CounterVec::new("kebab_votes_total", "Votes recorded by action", &["action"]),
+            rate_limited_votes: CounterVec::new(
+                "kebab_vote_rate_limited_total",
+                "Vote clicks rejected by the change cooldown by action",
+                &["action"],
+            ),
+            ineligible_votes: CounterVec::new(
+                "kebab_vote_ineligible_total",
+                "Vote clicks rejected by the guild's voter eligibility rules by reason",
+                &["reason"],
+            ),
+            interaction_duration: HistogramVec::new(
+                "kebab_interaction_duration_seconds",
+                "Time spent handling button interactions and slash commands",
+                &["kind", "name"],
+            ),
+            board_edits: CounterVec::new(
+                "kebab_board_edits_total",
+                "Board message edits by result",
+                &["result"],
+            ),
+            chart_render_duration: HistogramVec::new(
+                "kebab_chart_render_duration_seconds",
+                "Time spent rendering charts",
+                &["chart"],
+            ),
+            rollovers: AtomicU64::new(0),
+            discord_api_errors: CounterVec::new(
+                "kebab_discord_api_errors_total",
+                "Errors returned by the Discord API by operation",
+                &["operation"],
+            ),
+        }
+    }
+
+    /// 投票を1件記録する
+    pub fn record_vote(&self, action: &str) {
+        self.votes.inc(&[action]);
+    }
+
+    /// 連打対策で断った投票を1件記録する
+    pub fn record_rate_limited_vote(&self, action: &str) {
+        self.rate_limited_votes.inc(&[action]);
+    }
+
+    /// 投票の条件を満たさずに断った投票を1件記録する
+    pub fn record_ineligible_vote(&self, reason: &str) {
+        self.ineligible_votes.inc(&[reason]);
+    }
+
+    /// ボタン操作（`kind = "button"`）やコマンド（`kind = "command"`）の処理時間を記録する
+    pub fn observe_interaction(&self, kind: &str, name: &str, elapsed: Duration) {
+        self.interaction_duration
+            .observe(&[kind, name], elapsed.as_secs_f64());
+    }
+
+    /// 掲示板メッセージの編集結果を記録する
+    pub fn record_board_edit(&self, result: EditResult) {
+        self.board_edits.inc(&[result.code()]);
+    }
+
+    /// グラフの描画時間を記録する（`chart`はスタイル名または`comparison`）
+    pub fn observe_chart_render(&self, chart: &str, elapsed: Duration) {
+        self.chart_render_duration
+            .observe(&[chart], elapsed.as_secs_f64());
+    }
+
+    /// 投票期間の切り替えによるリセットを記録する
+    pub fn record_rollover(&self) {
+        self.rollovers.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Discord APIのエラーを記録する
+    pub fn record_discord_api_error(&self, operation: &str) {
+        self.discord_api_errors.inc(&[operation]);
+    }
+
+    /// Prometheusのテキスト形式で全てのメトリクスを書き出す
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        self.votes.render(&mut out);
+        self.rate_limited_votes.render(&mut out);
+        self.ineligible_votes.render(&mut out);
+        self.interaction_duration.render(&mut out);
+        self.board_edits.render(&mut out);
+        self.chart_render_duration.render(&mut out);
+        let _ = writeln!(
+            out,
+            "# HELP kebab_rollovers_total Vote resets caused by a new voting period"
+        );
+        let _ = writeln!(out, "# TYPE kebab_rollovers_total counter");
+        let _ = writeln!(
+            out,
+            "kebab_rollovers_total {}",
+            self.rollovers.load(Ordering::Relaxed)
+        );
+        self.discord_api_errors.render(&mut out);
+        out
+    }
+}
+
+/// ラベルごとのカウンター
+struct CounterVec {
+    name: &'static str,
+    help: &'static str,
+    labels: &'static [&'static str],
+    values: Mutex<BTreeMap<Vec<String>, u64>>,
+}
+
+impl CounterVec {
+    fn new(name: &'static str, help: &'static str, labels: &'static [&'static str]) -> Self {
+        Self {
+            name,
+            help,
+            labels,
+            values: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    fn inc(&self, label_values: &[&str]) {
+        let mut values = self.values.lock().unwrap_or_else(|e| e.into_inner());
+        *values.entry(to_key(label_values)).or_default() += 1;
+    }
+
+    fn render(&self, out: &mut String) {
+        let _ = writeln!(out, "# HELP {} {}", self.name, self.help);
+        let _ = writeln!(out, "# TYPE {} counter", self.name);
+        let values = self.values.lock().unwrap_or_else(|e| e.into_inner());
+        for (label_values, value) in values.iter() {
+            let _ = writeln!(
+                out,
+                "{}{} {}",
+                self.name,
+                format_labels(self.labels, label_values, None),
+                value
+            );
+        }
+    }
+}
+
+/// 1つのラベルの組み合わせのヒストグラム
+#[derive(Default)]
+struct Histogram {
+    /// `DURATION_BUCKETS`の各上限以下の観測数（累積ではない）
+    buckets: [u64; DURATION_BUCKETS.len()],
+    count: u64,
+    sum: f64,
+}
+
+/// ラベルごとのヒストグラム
+struct HistogramVec {
+    name: &'static str,
+    help: &'static str,
+    labels: &'static [&'static str],
+    values: Mutex<BTreeMap<Vec<String>, Histogram>>,
+}
+
+impl HistogramVec {
+    fn new(name: &'static str, help: &'static str, labels: &'static [&'static str]) -> Self {
+        Self {
+            name,
+            help,
+            labels,
+            values: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    fn observe(&self, label_values: &[&str], value: f64) {
+        let mut values = self.values.lock().unwrap_or_else(|e| e.into_inner());
+        let histogram = values.entry(to_key(label_values)).or_default();
+        if let Some(index) = DURATION_BUCKETS.iter().position(|bound| value <= *bound) {
+            histogram.buckets[index] += 1;
+        }
+        histogram.count += 1;
+        histogram.sum += value;
+    }
+
+    fn render(&self, out: &mut String) {
+        let _ = writeln!(out, "# HELP {} {}", self.name, self.help);
+        let _ = writeln!(out, "# TYPE {} histogram", self.name);
+        let values = self.values.lock().unwrap_or_else(|e| e.into_inner());
+        for (label_values, histogram) in values.iter() {
+            let mut cumulative = 0;
+            for (bound, count) in DURATION_BUCKETS.iter().zip(histogram.buckets) {
+                cumulative += count;
+                let _ = writeln!(
+                    out,
+                    "{}_bucket{} {}",
+                    self.name,
+                    format_labels(self.labels, label_values, Some(&bound.to_string())),
+                    cumulative
+                );
+            }
+            let _ = writeln!(
+                out,
+                "{}_bucket{} {}",
+                self.name,
+                format_labels(self.labels, label_values, Some("+Inf")),
+                histogram.count
+            );
+            let labels = format_labels(self.labels, label_values, None);
+            let _ = writeln!(out, "{}_sum{} {}", self.name, labels, histogram.sum);
+            let _ = writeln!(out, "{}_count{} {}", self.name, labels, histogram.count);
+        }
+    }
+}
+
+fn to_key(label_values: &[&str]) -> Vec<String> {
+    label_values.iter().map(|value| value.to_string()).collect()
+}
+
+/// `{name="value",...}`形式のラベルを作る（ヒストグラムのバケットでは`le`を追加する）
+fn format_labels(names: &[&str], values: &[String], le: Option<&str>) -> String {
+    let mut pairs: Vec<String> = names
+        .iter()
+        .zip(values)
+        .map(|(name, value)| format!("{}=\"{}\"", name, escape_label(value)))
+        .collect();
+    if let Some(le) = le {
+        pairs.push(format!("le=\"{}\"", le));
+    }
+
+    if pairs.is_empty() {
+        String::new()
+    } else {
+        format!("{{{}}}", pairs.join(","))
+    }
+}
+
+/// ラベルの値のバックスラッシュ・ダブルクォート・改行をエスケープする
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
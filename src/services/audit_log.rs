@@ -0,0 +1,295 @@
+use crate::metrics::METRICS;
+use crate::services::guild_settings_service::GuildSettingsService;
+use crate::services::vote_service::VoteService;
+use chrono::{DateTime, Utc};
+use poise::serenity_prelude::{ChannelId, CreateAllowedMentions, CreateMessage, Http};
+use sea_orm::DatabaseConnection;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::time::sleep;
+use tracing::{instrument, warn};
+
+/// 投稿を待つ記録の上限（超えた場合は古いものから捨てる）
+const MAX_PENDING_ENTRIES: usize = 5000;
+/// Discordのメッセージの最大文字数
+const MAX_MESSAGE_CHARS: usize = 2000;
+
+/// 監査ログに残す操作
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuditEvent {
+    /// 投票した・投票を変更した（初めての投票なら`old_action = None`）
+    VoteChanged {
+        user_id: i64,
+        old_action: Option<String>,
+        new_action: String,
+    },
+    /// 投票を取り消した
+    VoteRetracted { user_id: i64, old_action: String },
+    /// 掲示板を作成した
+    BoardCreated {
+        user_id: i64,
+        channel_id: i64,
+        message_id: i64,
+    },
+    /// 掲示板を別のチャンネル・メッセージに作り直した
+    BoardMoved {
+        user_id: i64,
+        old_channel_id: i64,
+        channel_id: i64,
+        message_id: i64,
+    },
+    /// `/reset_votes`で投票をリセットした
+    VotesReset { user_id: i64, deleted: u64 },
+    /// `/report`で報告を非表示にした・表示に戻した
+    ReportHidden {
+        user_id: i64,
+        report_id: i32,
+        hidden: bool,
+    },
+    /// `/reputation`でユーザーの信頼度を設定した・設定を外した（外した場合は`score = None`）
+    ReputationOverridden {
+        user_id: i64,
+        target_user_id: i64,
+        score: Option<u32>,
+    },
+    /// `/reputation`で信頼度による票の重み付けを切り替えた
+    ReputationWeightingChanged { user_id: i64, enabled: bool },
+    /// `/mod remove_vote`でユーザーの投票を削除した
+    VoteRemoved {
+        user_id: i64,
+        target_user_id: i64,
+        old_action: String,
+    },
+    /// `/mod ban_voter`で投票を禁止した（無期限なら`expires_at = None`）
+    VoterBanned {
+        user_id: i64,
+        target_user_id: i64,
+        expires_at: Option<DateTime<Utc>>,
+        reason: Option<String>,
+    },
+    /// `/mod unban_voter`で投票の禁止を解除した
+    VoterUnbanned { user_id: i64, target_user_id: i64 },
+}
+
+/// 監査ログの1件の記録
+#[derive(Debug, Clone)]
+pub struct AuditEntry {
+    pub guild_id: i64,
+    pub at: DateTime<Utc>,
+    pub event: AuditEvent,
+}
+
+/// 投稿を待つ監査ログ
+/// 操作のたびに投稿するとレート制限に掛かるため、記録を溜めておき一定間隔でサーバーごとにまとめて投稿する
+#[derive(Default)]
+pub struct AuditLog {
+    pending: Mutex<Vec<AuditEntry>>,
+}
+
+impl AuditLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 操作を記録する（投稿は次の`flush`で行う）
+    pub fn record(&self, guild_id: i64, event: AuditEvent) {
+        let mut pending = self.lock();
+        if pending.len() >= MAX_PENDING_ENTRIES {
+            warn!("投稿を待つ監査ログが多すぎるため、古い記録を破棄します");
+            pending.remove(0);
+        }
+        pending.push(AuditEntry {
+            guild_id,
+            at: Utc::now(),
+            event,
+        });
+    }
+
+    /// 溜まった記録を、監査ログのチャンネルが設定されているサーバーごとにまとめて投稿する
+    /// チャンネルが設定されていないサーバーの記録は捨てる
+    #[instrument(skip_all)]
+    pub async fn flush(&self, http: &Http, db: &DatabaseConnection, send_interval: Duration) {
+        let entries = std::mem::take(&mut *self.lock());
+        if entries.is_empty() {
+            return;
+        }
+
+        let mut by_guild: BTreeMap<i64, Vec<AuditEntry>> = BTreeMap::new();
+        for entry in entries {
+            by_guild.entry(entry.guild_id).or_default().push(entry);
+        }
+
+        let mut sent = false;
+        for (guild_id, entries) in by_guild {
+            let channel_id = match GuildSettingsService::get_audit_log_channel(db, guild_id).await {
+                Ok(Some(channel_id)) => ChannelId::new(channel_id as u64),
+                Ok(None) => continue,
+                Err(e) => {
+                    warn!(guild_id, error = %e, "監査ログのチャンネルを取得できませんでした");
+                    continue;
+                }
+            };
+
+            for content in Self::compose_messages(&entries) {
+                // Rate limit対策: 複数のメッセージを投稿する場合は間隔を空ける
+                if sent {
+                    sleep(send_interval).await;
+                }
+                sent = true;
+
+                // ユーザーやロールへの通知は送らない
+                let message = CreateMessage::new()
+                    .content(content)
+                    .allowed_mentions(CreateAllowedMentions::new());
+                if let Err(e) = channel_id.send_message(http, message).await {
+                    METRICS.record_discord_api_error("audit_log");
+                    warn!(guild_id, channel_id = channel_id.get(), error = %e, "監査ログの投稿に失敗しました");
+                }
+            }
+        }
+    }
+
+    /// 記録を1行ずつに整形し、メッセージの文字数の上限に収まるようにまとめる
+    fn compose_messages(entries: &[AuditEntry]) -> Vec<String> {
+        let mut messages = Vec::new();
+        let mut current = String::new();
+        for entry in entries {
+            let line = Self::format_entry(entry);
+            let length = current.chars().count() + line.chars().count() + 1;
+            if !current.is_empty() && length > MAX_MESSAGE_CHARS {
+                messages.push(std::mem::take(&mut current));
+            }
+            if !current.is_empty() {
+                current.push('\n');
+            }
+            current.push_str(&line);
+        }
+        if !current.is_empty() {
+            messages.push(current);
+        }
+        messages
+    }
+
+    /// 記録を1行に整形する（時刻は閲覧する人のタイムゾーンで表示される）
+    fn format_entry(entry: &AuditEntry) -> String {
+        let time = format!("<t:{}:T>", entry.at.timestamp());
+        match &entry.event {
+            AuditEvent::VoteChanged {
+                user_id,
+                old_action,
+                new_action,
+            } => format!(
+                "{} <@{}> 投票: {} → {}",
+                time,
+                user_id,
+                old_action
+                    .as_deref()
+                    .map(VoteService::action_label)
+                    .unwrap_or_else(|| "（なし）".to_string()),
+                VoteService::action_label(new_action)
+            ),
+            AuditEvent::VoteRetracted {
+                user_id,
+                old_action,
+            } => format!(
+                "{} <@{}> 投票を取り消し: {}",
+                time,
+                user_id,
+                VoteService::action_label(old_action)
+            ),
+            AuditEvent::BoardCreated {
+                user_id,
+                channel_id,
+                message_id,
+            } => format!(
+                "{} <@{}> 掲示板を作成: <#{}>（メッセージID: {}）",
+                time, user_id, channel_id, message_id
+            ),
+            AuditEvent::BoardMoved {
+                user_id,
+                old_channel_id,
+                channel_id,
+                message_id,
+            } => format!(
+                "{} <@{}> 掲示板を移動: <#{}> → <#{}>（メッセージID: {}）",
+                time, user_id, old_channel_id, channel_id, message_id
+            ),
+            AuditEvent::VotesReset { user_id, deleted } => format!(
+                "{} <@{}> /reset_votes: {}件の投票をリセット",
+                time, user_id, deleted
+            ),
+            AuditEvent::ReportHidden {
+                user_id,
+                report_id,
+                hidden,
+            } => format!(
+                "{} <@{}> 報告#{}を{}",
+                time,
+                user_id,
+                report_id,
+                if *hidden { "非表示" } else { "再表示" }
+            ),
+            AuditEvent::ReputationOverridden {
+                user_id,
+                target_user_id,
+                score,
+            } => match score {
+                Some(score) => format!(
+                    "{} <@{}> <@{}>の信頼度を{}に設定",
+                    time, user_id, target_user_id, score
+                ),
+                None => format!(
+                    "{} <@{}> <@{}>の信頼度の設定を解除",
+                    time, user_id, target_user_id
+                ),
+            },
+            AuditEvent::ReputationWeightingChanged { user_id, enabled } => format!(
+                "{} <@{}> 信頼度による票の重み付けを{}",
+                time,
+                user_id,
+                if *enabled { "有効化" } else { "無効化" }
+            ),
+            AuditEvent::VoteRemoved {
+                user_id,
+                target_user_id,
+                old_action,
+            } => format!(
+                "{} <@{}> <@{}>の投票を削除: {}",
+                time,
+                user_id,
+                target_user_id,
+                VoteService::action_label(old_action)
+            ),
+            AuditEvent::VoterBanned {
+                user_id,
+                target_user_id,
+                expires_at,
+                reason,
+            } => format!(
+                "{} <@{}> <@{}>の投票を禁止: {}{}",
+                time,
+                user_id,
+                target_user_id,
+                expires_at
+                    .map(|expires_at| format!("<t:{}:f>まで", expires_at.timestamp()))
+                    .unwrap_or_else(|| "無期限".to_string()),
+                reason
+                    .as_deref()
+                    .map(|reason| format!("（理由: {}）", reason))
+                    .unwrap_or_default()
+            ),
+            AuditEvent::VoterUnbanned {
+                user_id,
+                target_user_id,
+            } => format!(
+                "{} <@{}> <@{}>の投票の禁止を解除",
+                time, user_id, target_user_id
+            ),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<AuditEntry>> {
+        self.pending.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
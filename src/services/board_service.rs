@@ -1,5 +1,7 @@
 use crate::entities::board_data::{self, Model as BoardDataModel};
 use crate::entities::prelude::*;
+use crate::services::chart_service::ChartStyle;
+use crate::services::vote_service::VoteService;
 use chrono::Utc;
 use sea_orm::*;
 
@@ -96,6 +98,27 @@ impl BoardService {
         board_data.update(db).await
     }
 
+    /// サーバーの掲示板に表示するグラフのスタイルを更新
+    pub async fn update_board_chart_style(
+        db: &DatabaseConnection,
+        server_id: i64,
+        style: ChartStyle,
+    ) -> Result<Option<BoardDataModel>, DbErr> {
+        let Some(board_data) = Self::get_board_data_by_server_id(db, server_id)
+            .await?
+            .into_iter()
+            .next()
+        else {
+            return Ok(None);
+        };
+
+        let mut board_data: board_data::ActiveModel = board_data.into();
+        board_data.chart_style = Set(style.code().to_string());
+        board_data.updated_at = Set(Utc::now().into());
+
+        board_data.update(db).await.map(Some)
+    }
+
     /// ボードデータを削除
     pub async fn delete_board_data(
         db: &DatabaseConnection,
@@ -125,7 +148,7 @@ impl BoardService {
         since: chrono::DateTime<chrono::Utc>,
     ) -> Result<Vec<BoardDataModel>, DbErr> {
         BoardData::find()
-            .filter(board_data::Column::CreatedAt.gte(since))
+            .filter(board_data::Column::CreatedAt.gte(VoteService::to_db_timestamp(since)))
             .order_by_desc(board_data::Column::CreatedAt)
             .all(db)
             .await
@@ -1,13 +1,22 @@
-use crate::{Context, Error, services::*};
-use chrono::Datelike;
+use crate::metrics::{EditResult, METRICS};
+use crate::{Context, Data, Error, services::*};
+use chrono::{Datelike, Days};
 use poise::{
     CreateReply,
     serenity_prelude::{
-        ButtonStyle, ChannelId, Colour, CreateActionRow, CreateButton, CreateEmbed, EditMessage,
+        ButtonStyle, ChannelId, Colour, CreateActionRow, CreateAttachment, CreateButton,
+        CreateEmbed, EditAttachments, EditMessage, Http,
     },
 };
+use std::collections::{BTreeMap, btree_map::Entry};
 use std::time::Duration;
 use tokio::time::sleep;
+use tracing::{error, instrument, warn};
+
+/// 掲示板に添付するタイムラインチャートのファイル名
+pub const TIMELINE_CHART_FILENAME: &str = "vote_timeline.png";
+/// 信頼度で重み付けした投票結果の見出し
+pub const WEIGHTED_TALLY_TITLE: &str = "📊 投票結果（信頼度で重み付け）";
 
 pub struct BoardUIService;
 
@@ -34,147 +43,72 @@ impl BoardUIService {
         ctx: &Context<'_>,
         board_data: Vec<crate::entities::board_data::Model>,
     ) -> Result<String, Error> {
-        let mut response = String::from("保存された掲示板データ:\n");
-
-        // タイムラインチャートが存在するかチェック
-        let timeline_path = "vote_timeline.png";
-        let chart_exists = std::path::Path::new(timeline_path).exists();
-
-        // embedとボタンを一度だけ作成
-        let (embed, action_row) = Self::create_board_embed_and_buttons(ctx, chart_exists).await?;
-
-        for (index, data) in board_data.iter().enumerate() {
-            // Rate limit対策: 複数メッセージがある場合は間隔を空ける
-            if index > 0 {
-                sleep(Duration::from_millis(500)).await;
-            }
-
-            match Self::update_single_board_message(ctx, data, &embed, &action_row).await {
-                Ok(message) => response.push_str(&message),
-                Err(e) => {
-                    response.push_str(&format!(
-                        "メッセージID: {} の更新中にエラーが発生しました: {}\n",
-                        data.message_id, e
-                    ));
-                }
-            }
-        }
-
-        Ok(response)
+        Self::update_all_board_messages_serenity(ctx.serenity_context(), board_data, ctx.data())
+            .await
     }
 
-    /// 単一の掲示板メッセージを更新する
-    async fn update_single_board_message(
-        ctx: &Context<'_>,
-        data: &crate::entities::board_data::Model,
-        embed: &CreateEmbed,
-        action_row: &CreateActionRow,
-    ) -> Result<String, Error> {
-        let channel = ctx
-            .serenity_context()
-            .http
-            .get_channel(ChannelId::new(data.channel_id as u64))
+    /// ユーザー本人の現在の投票と、今週のこれまでの期間の投票を表示するEmbedを作成する
+    pub async fn create_my_vote_embed(
+        database: &sea_orm::DatabaseConnection,
+        user_id: i64,
+    ) -> Result<CreateEmbed, Error> {
+        let period = VoteService::get_current_jst_afternoon_period();
+        let week_start = period - Days::new(period.weekday().num_days_from_monday() as u64);
+
+        let current = match VoteService::get_vote(database, user_id).await? {
+            Some(vote) => format!(
+                "{}\n投票日時: <t:{}:F>（<t:{}:R>）",
+                VoteService::action_label(&vote.action),
+                vote.updated_at.timestamp(),
+                vote.updated_at.timestamp()
+            ),
+            None => "まだ投票していません。".to_string(),
+        };
+
+        // 今週の過去の期間ごとの最後の記録（取り消しを含む）
+        let mut past: BTreeMap<chrono::NaiveDate, String> = BTreeMap::new();
+        if week_start < period {
+            let history = VoteService::get_user_vote_history(
+                database,
+                user_id,
+                week_start,
+                period - Days::new(1),
+            )
             .await?;
-
-        if let poise::serenity_prelude::Channel::Guild(channel) = channel {
-            let mut message = channel
-                .message(&ctx.serenity_context().http, data.message_id as u64)
-                .await?;
-
-            let timeline_path = "vote_timeline.png";
-            let chart_exists = std::path::Path::new(timeline_path).exists();
-
-            let mut msg = EditMessage::new()
-                .content("")
-                .embed(embed.clone())
-                .components(vec![action_row.clone()]);
-
-            // チャートが存在する場合はファイルを添付
-            if chart_exists {
-                if let Ok(attachment) =
-                    poise::serenity_prelude::CreateAttachment::path(timeline_path).await
-                {
-                    msg = msg.attachments(
-                        poise::serenity_prelude::EditAttachments::new().add(attachment),
-                    );
-                }
+            for entry in history {
+                past.insert(entry.period_date, entry.action);
             }
-
-            message.edit(&ctx.serenity_context().http, msg).await?;
-
-            Ok(format!(
-                "メッセージID: {} を編集しました。\n",
-                data.message_id
-            ))
-        } else {
-            Err("ギルドチャンネルではありません。".into())
         }
-    }
-
-    /// 掲示板のEmbedとボタンを作成する
-    pub async fn create_board_embed_and_buttons(
-        ctx: &Context<'_>,
-        chart_exists: bool,
-    ) -> Result<(CreateEmbed, CreateActionRow), Error> {
-        let now = chrono::Utc::now();
-        let date = now.date_naive();
-        let date_str = date.format("%m/%d").to_string();
-
-        // 曜日の取得
-        let weekday = now.weekday();
-        let weekday_str = Self::get_weekday_string(weekday);
-
-        // ボタンの作成
-        let found_button = CreateButton::new("found")
-            .label("営業してる")
-            .style(ButtonStyle::Primary);
-        let not_found_button = CreateButton::new("not_found")
-            .label("いない")
-            .style(ButtonStyle::Secondary);
-        let sold_out_button = CreateButton::new("sold_out")
-            .label("売り切れた")
-            .style(ButtonStyle::Danger);
-        let action_row =
-            CreateActionRow::Buttons(vec![found_button, not_found_button, sold_out_button]);
-
-        // 投票結果を並行して取得
-        let (found_count, not_found_count, sold_out_count) = tokio::try_join!(
-            VoteService::count_votes_by_action(&ctx.data().database, "found".to_string()),
-            VoteService::count_votes_by_action(&ctx.data().database, "not_found".to_string()),
-            VoteService::count_votes_by_action(&ctx.data().database, "sold_out".to_string()),
-        )?;
-
-        // 最新の投票更新日時を取得
-        let last_vote_updated_at = VoteService::get_latest_vote_updated_at(&ctx.data().database)
-            .await?
-            .unwrap_or(now);
-
-        let mut embed = CreateEmbed::new()
-            .title(format!("{}({})のケバブ情報掲示板", date_str, weekday_str))
-            .description(format!(
-                "**📊 投票結果**\n\
-                🥙 営業してる: {}票\n\
-                ❌ いない: {}票\n\
-                🚫 売り切れた: {}票\n\n\
-                更新日時: <t:{}:F>",
-                found_count,
-                not_found_count,
-                sold_out_count,
-                last_vote_updated_at.timestamp()
-            ))
-            .colour(Colour::from_rgb(0, 255, 0))
-            .timestamp(now);
-
-        // チャートが存在する場合はEmbedに画像を設定
-        if chart_exists {
-            embed = embed.image("attachment://vote_timeline.png");
-        }
-
-        Ok((embed, action_row))
+        let past = if past.is_empty() {
+            "今週のこれまでの投票はありません。".to_string()
+        } else {
+            past.iter()
+                .map(|(date, action)| {
+                    let label = if action == RETRACT_ACTION {
+                        "↩️ 取り消し".to_string()
+                    } else {
+                        VoteService::action_label(action)
+                    };
+                    format!(
+                        "{}({}) {}",
+                        date.format("%m/%d"),
+                        Self::get_weekday_string(date.weekday()),
+                        label
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+
+        Ok(CreateEmbed::new()
+            .title("🗳️ あなたの投票")
+            .field("現在の投票", current, false)
+            .field("今週のこれまでの投票", past, false)
+            .colour(Colour::from_rgb(52, 152, 219)))
     }
 
     /// 曜日を日本語文字列に変換する
-    fn get_weekday_string(weekday: chrono::Weekday) -> &'static str {
+    pub fn get_weekday_string(weekday: chrono::Weekday) -> &'static str {
         match weekday {
             chrono::Weekday::Mon => "月",
             chrono::Weekday::Tue => "火",
@@ -191,7 +125,8 @@ impl BoardUIService {
         ctx: &poise::serenity_prelude::Context,
         data: &crate::entities::board_data::Model,
         embed: &CreateEmbed,
-        action_row: &CreateActionRow,
+        action_rows: &[CreateActionRow],
+        chart: Option<Vec<u8>>,
     ) -> Result<String, Error> {
         let channel = ctx
             .http
@@ -201,23 +136,24 @@ impl BoardUIService {
         if let poise::serenity_prelude::Channel::Guild(channel) = channel {
             let mut message = channel.message(&ctx.http, data.message_id as u64).await?;
 
-            let timeline_path = "vote_timeline.png";
-            let chart_exists = std::path::Path::new(timeline_path).exists();
-
             let mut msg = EditMessage::new()
                 .content("")
-                .embed(embed.clone())
-                .components(vec![action_row.clone()]);
-
-            // チャートが存在する場合はファイルを添付
-            if chart_exists {
-                if let Ok(attachment) =
-                    poise::serenity_prelude::CreateAttachment::path(timeline_path).await
-                {
-                    msg = msg.attachments(
-                        poise::serenity_prelude::EditAttachments::new().add(attachment),
+                .components(action_rows.to_vec());
+
+            // チャートが生成できた場合はファイルを添付してEmbedに画像を設定
+            if let Some(chart) = chart {
+                msg = msg
+                    .embed(
+                        embed
+                            .clone()
+                            .image(format!("attachment://{}", TIMELINE_CHART_FILENAME)),
+                    )
+                    .attachments(
+                        EditAttachments::new()
+                            .add(CreateAttachment::bytes(chart, TIMELINE_CHART_FILENAME)),
                     );
-                }
+            } else {
+                msg = msg.embed(embed.clone());
             }
 
             message.edit(&ctx.http, msg).await?;
@@ -227,35 +163,99 @@ impl BoardUIService {
                 data.message_id
             ))
         } else {
-            Err("ギルドチャンネルではありません。".into())
+            Err(Error::InvalidInput(
+                "ギルドチャンネルではありません。".to_string(),
+            ))
         }
     }
 
+    /// 保存されている全ての掲示板メッセージを最新の投票結果で更新する（Serenity Context用）
+    /// 掲示板がなければ何もしない
+    pub async fn refresh_all_board_messages_serenity(
+        ctx: &poise::serenity_prelude::Context,
+        app_data: &Data,
+    ) -> Result<(), Error> {
+        let board_data = BoardService::get_all_board_data(&app_data.database).await?;
+        if board_data.is_empty() {
+            return Ok(());
+        }
+
+        Self::update_all_board_messages_serenity(ctx, board_data, app_data).await?;
+        Ok(())
+    }
+
     /// 全ての掲示板メッセージを更新する（Serenity Context用）
+    #[instrument(skip_all, fields(boards = board_data.len()))]
     pub async fn update_all_board_messages_serenity(
         ctx: &poise::serenity_prelude::Context,
         board_data: Vec<crate::entities::board_data::Model>,
-        database: &sea_orm::DatabaseConnection,
+        app_data: &Data,
     ) -> Result<String, Error> {
+        let database = app_data.database.as_ref();
         let mut response = String::from("保存された掲示板データ:\n");
 
-        // タイムラインチャートが存在するかチェック
-        let timeline_path = "vote_timeline.png";
-        let chart_exists = std::path::Path::new(timeline_path).exists();
-
-        // embedとボタンを一度だけ作成
-        let (embed, action_row) =
-            Self::create_board_embed_and_buttons_serenity(database, chart_exists).await?;
+        // 報告と重み付けした票数はサーバーごとに表示するため、embedとボタンはサーバーごとに一度だけ作成
+        let mut guild_embeds: BTreeMap<i64, (CreateEmbed, Vec<CreateActionRow>)> = BTreeMap::new();
 
         for (index, data) in board_data.iter().enumerate() {
             // Rate limit対策: 複数メッセージがある場合は間隔を空ける
             if index > 0 {
-                sleep(Duration::from_millis(500)).await;
+                sleep(Duration::from_millis(
+                    app_data.config.board.edit_interval_ms,
+                ))
+                .await;
             }
 
-            match Self::update_single_board_message_serenity(ctx, data, &embed, &action_row).await {
-                Ok(message) => response.push_str(&message),
+            // 1つのサーバーのembedを作れなくても、他のサーバーの掲示板は更新する
+            let (embed, action_rows) = match guild_embeds.entry(data.server_id) {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(entry) => match Self::create_board_embed_and_buttons_serenity(
+                    &ctx.http,
+                    database,
+                    &app_data.reputation_cache,
+                    data.server_id,
+                )
+                .await
+                {
+                    Ok(embed) => entry.insert(embed),
+                    Err(e) => {
+                        METRICS.record_board_edit(EditResult::Failure);
+                        warn!(
+                            guild_id = data.server_id,
+                            message_id = data.message_id,
+                            error = %e,
+                            "掲示板のembedの作成中にエラーが発生しました"
+                        );
+                        response.push_str(&format!(
+                            "メッセージID: {} のembedの作成中にエラーが発生しました: {}\n",
+                            data.message_id, e
+                        ));
+                        continue;
+                    }
+                },
+            };
+
+            // チャートはサーバーごとのテーマで生成する
+            let chart = Self::render_board_chart(app_data, data).await;
+
+            match Self::update_single_board_message_serenity(ctx, data, embed, action_rows, chart)
+                .await
+            {
+                Ok(message) => {
+                    METRICS.record_board_edit(EditResult::Success);
+                    response.push_str(&message);
+                }
                 Err(e) => {
+                    METRICS.record_board_edit(EditResult::Failure);
+                    if matches!(e, Error::Discord(_)) {
+                        METRICS.record_discord_api_error("board_edit");
+                    }
+                    warn!(
+                        guild_id = data.server_id,
+                        message_id = data.message_id,
+                        error = %e,
+                        "掲示板の更新中にエラーが発生しました"
+                    );
                     response.push_str(&format!(
                         "メッセージID: {} の更新中にエラーが発生しました: {}\n",
                         data.message_id, e
@@ -267,11 +267,41 @@ impl BoardUIService {
         Ok(response)
     }
 
-    /// 掲示板のEmbedとボタンを作成する（データベース直接アクセス用）
+    /// 掲示板に設定されたスタイルとサーバー設定のテーマでタイムラインチャートを取得する
+    /// 設定でチャートが無効な場合や生成に失敗した場合は、チャートなしで掲示板を更新できるように`None`を返す
+    async fn render_board_chart(
+        app_data: &Data,
+        data: &crate::entities::board_data::Model,
+    ) -> Option<Vec<u8>> {
+        let config = &app_data.config;
+        if !config.features.board_chart {
+            return None;
+        }
+
+        match ChartService::get_or_generate_timeline_chart(
+            &app_data.database,
+            &app_data.chart_cache,
+            Some(data.server_id),
+            ChartStyle::from_code(&data.chart_style).unwrap_or(config.chart.style),
+            &ChartOutput::png(config.chart.width, config.chart.height, 1),
+        )
+        .await
+        {
+            Ok(chart) => Some(chart.as_ref().clone()),
+            Err(e) => {
+                error!(guild_id = data.server_id, error = %e, "タイムラインチャート生成エラー");
+                None
+            }
+        }
+    }
+
+    /// サーバーの掲示板のEmbedとボタンを作成する（データベース直接アクセス用）
     pub async fn create_board_embed_and_buttons_serenity(
+        http: &Http,
         database: &sea_orm::DatabaseConnection,
-        chart_exists: bool,
-    ) -> Result<(CreateEmbed, CreateActionRow), Error> {
+        reputation_cache: &ReputationCache,
+        guild_id: i64,
+    ) -> Result<(CreateEmbed, Vec<CreateActionRow>), Error> {
         let now = chrono::Utc::now();
         let date = now.date_naive();
         let date_str = date.format("%m/%d").to_string();
@@ -290,42 +320,87 @@ impl BoardUIService {
         let sold_out_button = CreateButton::new("sold_out")
             .label("売り切れた")
             .style(ButtonStyle::Danger);
-        let action_row =
-            CreateActionRow::Buttons(vec![found_button, not_found_button, sold_out_button]);
-
-        // 投票結果を並行して取得
-        let (found_count, not_found_count, sold_out_count) = tokio::try_join!(
-            VoteService::count_votes_by_action(database, "found".to_string()),
-            VoteService::count_votes_by_action(database, "not_found".to_string()),
-            VoteService::count_votes_by_action(database, "sold_out".to_string()),
-        )?;
+        let report_button = CreateButton::new("report")
+            .label("報告")
+            .style(ButtonStyle::Success);
+        let retract_button = CreateButton::new("retract")
+            .label("取り消し")
+            .style(ButtonStyle::Secondary);
+        let my_vote_button = CreateButton::new("my_vote")
+            .label("自分の投票")
+            .style(ButtonStyle::Secondary);
+        let action_rows = vec![
+            CreateActionRow::Buttons(vec![found_button, not_found_button, sold_out_button]),
+            CreateActionRow::Buttons(vec![report_button, retract_button, my_vote_button]),
+        ];
+
+        // このサーバーの投票結果（サーバーで重み付けが有効なら、信頼度で重み付けした票数）
+        let weighted =
+            ReputationService::get_weighted_tally(database, reputation_cache, guild_id).await?;
+        let tally_section = match weighted {
+            Some(tally) => format!("**{}**\n{}", WEIGHTED_TALLY_TITLE, tally.describe()),
+            None => {
+                let counts = VoteService::count_guild_votes(database, guild_id).await?;
+                let lines: Vec<String> = VOTE_OPTIONS
+                    .iter()
+                    .zip(counts)
+                    .map(|(option, count)| {
+                        format!("{} {}: {}票", option.emoji, option.label, count)
+                    })
+                    .collect();
+                format!("**📊 投票結果**\n{}", lines.join("\n"))
+            }
+        };
 
         // 最新の投票更新日時を取得
         let last_vote_updated_at = VoteService::get_latest_vote_updated_at(database)
             .await?
             .unwrap_or(now);
 
+        // 現在の投票期間にこのサーバーで行われた最近の報告と最新の写真
+        let period = VoteService::get_current_jst_afternoon_period();
+        let (reports, photo) = tokio::try_join!(
+            ReportService::get_latest_visible_reports(
+                database,
+                guild_id,
+                period,
+                BOARD_REPORT_LIMIT
+            ),
+            ReportService::get_latest_visible_photo(database, guild_id, period),
+        )?;
+        let reports_section = if reports.is_empty() {
+            String::new()
+        } else {
+            let lines: Vec<String> = reports.iter().map(ReportService::format_report).collect();
+            format!("**📝 最近の報告**\n{}\n\n", lines.join("\n"))
+        };
+
         let mut embed = CreateEmbed::new()
             .title(format!("{}({})のケバブ情報掲示板", date_str, weekday_str))
             .description(format!(
-                "**📊 投票結果**\n\
-                🥙 営業してる: {}票\n\
-                ❌ いない: {}票\n\
-                🚫 売り切れた: {}票\n\n\
-                更新日時: <t:{}:F>",
-                found_count,
-                not_found_count,
-                sold_out_count,
+                "{}\n\n{}更新日時: <t:{}:F>",
+                tally_section,
+                reports_section,
                 last_vote_updated_at.timestamp()
             ))
             .colour(Colour::from_rgb(0, 255, 0))
             .timestamp(now);
 
-        // チャートが存在する場合はEmbedに画像を設定
-        if chart_exists {
-            embed = embed.image("attachment://vote_timeline.png");
+        // 最新の写真をサムネイルに表示する（期限の切れたURLは表示されないため、先に取得し直す）
+        let photo = match photo {
+            Some(report) => match ReportService::refresh_photo_url(http, database, report).await {
+                Ok(report) => Some(report),
+                Err(e) => {
+                    warn!(guild_id, error = %e, "写真のURLを取得し直せませんでした");
+                    None
+                }
+            },
+            None => None,
+        };
+        if let Some(photo_url) = photo.and_then(|report| report.photo_url) {
+            embed = embed.thumbnail(photo_url);
         }
 
-        Ok((embed, action_row))
+        Ok((embed, action_rows))
     }
 }
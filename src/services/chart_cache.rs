@@ -0,0 +1,77 @@
+use crate::services::chart_service::{ChartOutput, ChartStyle};
+use chrono::{DateTime, NaiveDate, Utc};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// キャッシュに保持するグラフの最大数（超えた場合は全て破棄する）
+const MAX_CACHED_CHARTS: usize = 64;
+
+/// キャッシュ対象のグラフの種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChartKind {
+    /// 現在の期間のタイムライングラフ
+    Timeline(ChartStyle),
+}
+
+/// 投票データの状態（件数と最新の更新日時）
+/// 投票の追加・変更・削除のいずれでも値が変わる
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct VoteDataVersion {
+    pub count: u64,
+    pub latest_updated_at: Option<DateTime<Utc>>,
+}
+
+/// グラフキャッシュのキー
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ChartCacheKey {
+    /// テーマの適用されたサーバー（DMなどサーバー外は`None`）
+    pub guild_id: Option<i64>,
+    pub period: NaiveDate,
+    pub kind: ChartKind,
+    pub output: ChartOutput,
+    pub version: VoteDataVersion,
+}
+
+/// 描画済みのグラフを保持するキャッシュ
+/// 同じデータに対する複数の掲示板や`/vote_chart`で描画結果を共有する
+#[derive(Default)]
+pub struct ChartCache {
+    entries: Mutex<HashMap<ChartCacheKey, Arc<Vec<u8>>>>,
+}
+
+impl ChartCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// キャッシュ済みのグラフを取得
+    pub fn get(&self, key: &ChartCacheKey) -> Option<Arc<Vec<u8>>> {
+        self.lock().get(key).cloned()
+    }
+
+    /// 描画したグラフをキャッシュに保存
+    pub fn insert(&self, key: ChartCacheKey, chart: Vec<u8>) -> Arc<Vec<u8>> {
+        let chart = Arc::new(chart);
+        let mut entries = self.lock();
+        if entries.len() >= MAX_CACHED_CHARTS {
+            entries.clear();
+        }
+        entries.insert(key, Arc::clone(&chart));
+        chart
+    }
+
+    /// 投票データが変わった時に全てのグラフを破棄する
+    pub fn invalidate(&self) {
+        self.lock().clear();
+    }
+
+    /// テーマが変わった時などに特定のサーバーのグラフを破棄する
+    pub fn invalidate_guild(&self, guild_id: i64) {
+        self.lock().retain(|key, _| key.guild_id != Some(guild_id));
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<ChartCacheKey, Arc<Vec<u8>>>> {
+        // 描画中にパニックしてもキャッシュ自体は壊れないので汚染を無視する
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
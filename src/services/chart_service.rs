@@ -1,9 +1,144 @@
+use crate::Error;
+use crate::config::ChartConfig;
 use crate::entities::vote::Model as VoteModel;
-use chrono::Timelike;
-use chrono_tz::Asia::Tokyo;
+use crate::metrics::METRICS;
+use crate::services::chart_cache::{ChartCache, ChartCacheKey, ChartKind};
+use crate::services::chart_theme::ChartTheme;
+use crate::services::guild_settings_service::GuildSettingsService;
+use crate::services::vote_service::{VOTE_OPTIONS, VoteOption, VoteService};
+use chrono::{Datelike, NaiveDate, Timelike};
+use plotters::coord::Shift;
+use plotters::coord::types::RangedCoordf32;
 use plotters::prelude::*;
-use plotters::style::RGBColor;
-use std::collections::HashMap;
+use sea_orm::DatabaseConnection;
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+use std::sync::{Arc, OnceLock};
+use std::time::Instant;
+use tracing::{debug, instrument};
+
+/// 描画処理の中で発生するエラー（公開する関数では`Error::Chart`に包む）
+type ChartError = Box<dyn std::error::Error + Send + Sync>;
+/// グラフに描画する（X座標, Y座標）の系列
+type Series = Vec<(f32, f32)>;
+type TimeChart<'a, DB> = ChartContext<'a, DB, Cartesian2d<RangedCoordf32, RangedCoordf32>>;
+
+/// グラフのX軸に表示する時間帯の既定値（投票期間のタイムゾーン、開始時は含み終了時は含まない）
+const DEFAULT_CHART_HOURS: Range<u32> = 14..20;
+/// 起動時に設定されたグラフのX軸に表示する時間帯
+static CHART_HOURS: OnceLock<Range<u32>> = OnceLock::new();
+/// 棒グラフで投票数を集計する間隔（分）
+const BAR_BUCKET_MINUTES: usize = 15;
+
+/// 現在の期間のタイムライングラフの描画スタイル
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChartStyle {
+    /// 選択肢ごとの累積投票数の折れ線グラフ
+    #[default]
+    Line,
+    /// 15分ごとの投票数の棒グラフ
+    Bar,
+    /// 選択肢ごとの投票の割合の積み上げ面グラフ
+    StackedArea,
+}
+
+impl ChartStyle {
+    /// データベースに保存された値から変換（不明な値は`None`）
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "line" => Some(ChartStyle::Line),
+            "bar" => Some(ChartStyle::Bar),
+            "stacked_area" => Some(ChartStyle::StackedArea),
+            _ => None,
+        }
+    }
+
+    /// データベースに保存する値
+    pub fn code(&self) -> &'static str {
+        match self {
+            ChartStyle::Line => "line",
+            ChartStyle::Bar => "bar",
+            ChartStyle::StackedArea => "stacked_area",
+        }
+    }
+}
+
+/// グラフの出力形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChartFormat {
+    Png,
+    Svg,
+}
+
+impl ChartFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ChartFormat::Png => "png",
+            ChartFormat::Svg => "svg",
+        }
+    }
+}
+
+/// グラフの出力設定
+/// `width`と`height`は論理サイズで、PNGの場合は`scale`倍の解像度で描画する
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ChartOutput {
+    pub format: ChartFormat,
+    pub width: u32,
+    pub height: u32,
+    pub scale: u32,
+}
+
+impl Default for ChartOutput {
+    fn default() -> Self {
+        Self::png(1200, 600, 1)
+    }
+}
+
+impl ChartOutput {
+    pub fn png(width: u32, height: u32, scale: u32) -> Self {
+        Self {
+            format: ChartFormat::Png,
+            width,
+            height,
+            scale: scale.max(1),
+        }
+    }
+
+    pub fn svg(width: u32, height: u32) -> Self {
+        Self {
+            format: ChartFormat::Svg,
+            width,
+            height,
+            scale: 1,
+        }
+    }
+
+    /// 実際に描画する画像のサイズ
+    pub fn pixel_size(&self) -> (u32, u32) {
+        (self.width * self.scale, self.height * self.scale)
+    }
+
+    /// 拡張子付きのファイル名
+    pub fn filename(&self, stem: &str) -> String {
+        format!("{}.{}", stem, self.format.extension())
+    }
+}
+
+/// 描画するグラフの内容
+enum ChartContent<'a> {
+    /// 現在の期間の選択肢ごとの累積投票数
+    Timeline {
+        votes: &'a [VoteModel],
+        style: ChartStyle,
+    },
+    /// 複数期間の指定した選択肢の累積投票数
+    Comparison {
+        periods: &'a [(NaiveDate, Vec<VoteModel>)],
+        option: &'a VoteOption,
+    },
+}
 
 pub struct ChartService;
 
@@ -11,147 +146,605 @@ impl ChartService {
     /// 投票データから時系列グラフを生成（時間ベース）
     pub async fn generate_vote_timeline_chart(
         votes: Vec<VoteModel>,
-        output_path: &str,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        // 12時から24時まで1分間隔の時間ラベルを準備
+        style: ChartStyle,
+        theme: &ChartTheme,
+        output: &ChartOutput,
+    ) -> Result<Vec<u8>, Error> {
+        let started = Instant::now();
+        let chart = Self::render(
+            &ChartContent::Timeline {
+                votes: &votes,
+                style,
+            },
+            theme,
+            output,
+        );
+        METRICS.observe_chart_render(style.code(), started.elapsed());
+        chart.map_err(Error::Chart)
+    }
+
+    /// 現在の期間のタイムライングラフをサーバーのテーマで取得する
+    /// 投票データが前回の描画から変わっていなければキャッシュ済みの画像を返す
+    #[instrument(skip(db, cache, output))]
+    pub async fn get_or_generate_timeline_chart(
+        db: &DatabaseConnection,
+        cache: &ChartCache,
+        guild_id: Option<i64>,
+        style: ChartStyle,
+        output: &ChartOutput,
+    ) -> Result<Arc<Vec<u8>>, Error> {
+        let key = ChartCacheKey {
+            guild_id,
+            period: VoteService::get_current_jst_afternoon_period(),
+            kind: ChartKind::Timeline(style),
+            output: *output,
+            version: VoteService::get_vote_data_version(db).await?,
+        };
+        if let Some(chart) = cache.get(&key) {
+            debug!("キャッシュ済みのグラフを使用します");
+            return Ok(chart);
+        }
+
+        let votes = VoteService::get_all_votes(db).await?;
+        let theme = GuildSettingsService::get_chart_theme(db, guild_id).await?;
+        let chart = Self::generate_vote_timeline_chart(votes, style, &theme, output).await?;
+        Ok(cache.insert(key, chart))
+    }
+
+    /// 複数の投票期間の累積投票数を同じ時刻軸に重ねて比較するグラフを生成
+    /// `periods`は古い順に並べた（期間の日付, その期間の最終投票データ）の組
+    pub async fn generate_vote_comparison_chart(
+        periods: Vec<(NaiveDate, Vec<VoteModel>)>,
+        option: &VoteOption,
+        theme: &ChartTheme,
+        output: &ChartOutput,
+    ) -> Result<Vec<u8>, Error> {
+        let started = Instant::now();
+        let chart = Self::render(
+            &ChartContent::Comparison {
+                periods: &periods,
+                option,
+            },
+            theme,
+            output,
+        );
+        METRICS.observe_chart_render("comparison", started.elapsed());
+        chart.map_err(Error::Chart)
+    }
+
+    /// 出力形式に合わせた描画先を用意してグラフを描画し、画像のバイト列を返す
+    fn render(
+        content: &ChartContent<'_>,
+        theme: &ChartTheme,
+        output: &ChartOutput,
+    ) -> Result<Vec<u8>, ChartError> {
+        let (width, height) = output.pixel_size();
+        // 高解像度で描画する場合は文字や線も同じ倍率で拡大する
+        let theme = theme.clone().with_font_scale(output.scale as f64);
+
+        match output.format {
+            ChartFormat::Png => {
+                let mut buffer = vec![0u8; (width * height * 3) as usize];
+                {
+                    let root = BitMapBackend::with_buffer(&mut buffer, (width, height))
+                        .into_drawing_area();
+                    Self::draw(&root, content, &theme, output.scale)?;
+                    root.present()?;
+                }
+                Self::encode_png(buffer, width, height)
+            }
+            ChartFormat::Svg => {
+                let mut svg = String::new();
+                {
+                    let root =
+                        SVGBackend::with_string(&mut svg, (width, height)).into_drawing_area();
+                    Self::draw(&root, content, &theme, output.scale)?;
+                    root.present()?;
+                }
+                Ok(svg.into_bytes())
+            }
+        }
+    }
+
+    /// 描画先の種類によらずグラフを描画する
+    fn draw<DB: DrawingBackend>(
+        root: &DrawingArea<DB, Shift>,
+        content: &ChartContent<'_>,
+        theme: &ChartTheme,
+        scale: u32,
+    ) -> Result<(), ChartError>
+    where
+        DB::ErrorType: 'static,
+    {
+        let px = |value: u32| value * scale;
+
+        root.fill(&theme.background)?;
+
+        // エリアを分割（上：グラフエリア、下：ラベルエリア）
+        let (upper, lower) = root.split_vertically((94).percent());
+
+        match content {
+            ChartContent::Timeline { votes, style } => match style {
+                ChartStyle::Line => Self::draw_line_timeline(&upper, &lower, votes, theme, scale)?,
+                ChartStyle::Bar => Self::draw_bar_timeline(&upper, &lower, votes, theme, scale)?,
+                ChartStyle::StackedArea => {
+                    Self::draw_stacked_area_timeline(&upper, &lower, votes, theme, scale)?
+                }
+            },
+            ChartContent::Comparison { periods, option } => {
+                let series: Vec<(NaiveDate, Series, Series)> = periods
+                    .iter()
+                    .map(|(date, votes)| {
+                        let (data, changes) = Self::cumulative_series(votes, option.action);
+                        (*date, data, changes)
+                    })
+                    .collect();
+
+                let y_max = Self::cumulative_y_max(series.iter().map(|(_, data, _)| data));
+
+                let caption = theme.locale.comparison_caption(option);
+                let mut chart = Self::build_time_chart(
+                    &upper,
+                    &lower,
+                    &caption,
+                    theme.locale.y_desc(ChartStyle::Line),
+                    y_max,
+                    theme,
+                    scale,
+                )?;
+
+                let latest_index = series.len().saturating_sub(1);
+                for (index, (date, data, changes)) in series.into_iter().enumerate() {
+                    let color = theme.period_color(index);
+                    // 最新の期間は太線で強調する
+                    let style = if index == latest_index {
+                        color.stroke_width(px(3))
+                    } else {
+                        color.stroke_width(px(1))
+                    };
+                    let label = format!(
+                        "{}({})",
+                        date.format("%m/%d"),
+                        theme.locale.weekday(date.weekday())
+                    );
+
+                    chart
+                        .draw_series(LineSeries::new(data, style))?
+                        .label(label)
+                        .legend(move |(x, y)| {
+                            PathElement::new(vec![(x, y), (x + px(10) as i32, y)], style)
+                        });
+
+                    chart.draw_series(
+                        changes
+                            .iter()
+                            .map(|(x, y)| Circle::new((*x, *y), px(2), color.filled())),
+                    )?;
+                }
+
+                Self::draw_legend(&mut chart, theme)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 選択肢ごとの累積投票数を折れ線グラフで描画する
+    fn draw_line_timeline<DB: DrawingBackend>(
+        upper: &DrawingArea<DB, Shift>,
+        lower: &DrawingArea<DB, Shift>,
+        votes: &[VoteModel],
+        theme: &ChartTheme,
+        scale: u32,
+    ) -> Result<(), ChartError>
+    where
+        DB::ErrorType: 'static,
+    {
+        let px = |value: u32| value * scale;
+        let series: Vec<(Series, Series)> = VOTE_OPTIONS
+            .iter()
+            .map(|option| Self::cumulative_series(votes, option.action))
+            .collect();
+
+        let y_max = Self::cumulative_y_max(series.iter().map(|(data, _)| data));
+
+        let mut chart = Self::build_time_chart(
+            upper,
+            lower,
+            theme.locale.timeline_caption(),
+            theme.locale.y_desc(ChartStyle::Line),
+            y_max,
+            theme,
+            scale,
+        )?;
+
+        // 選択肢ごとに累積折れ線グラフを描画
+        for ((option, color), (data, data_with_changes)) in
+            VOTE_OPTIONS.iter().zip(theme.series).zip(series)
+        {
+            let style = color.stroke_width(px(1));
+
+            // 折れ線グラフを描画
+            chart
+                .draw_series(LineSeries::new(data, style))?
+                .label(theme.locale.option_label(option))
+                .legend(move |(x, y)| {
+                    PathElement::new(vec![(x, y), (x + px(10) as i32, y)], style)
+                });
+
+            // 値が変わったポイントのみマーク
+            chart.draw_series(
+                data_with_changes
+                    .iter()
+                    .map(|(x, y)| Circle::new((*x, *y), px(3), color.filled())),
+            )?;
+        }
+
+        Self::draw_legend(&mut chart, theme)
+    }
+
+    /// 15分ごとの投票数を選択肢ごとに並べた棒グラフで描画する
+    fn draw_bar_timeline<DB: DrawingBackend>(
+        upper: &DrawingArea<DB, Shift>,
+        lower: &DrawingArea<DB, Shift>,
+        votes: &[VoteModel],
+        theme: &ChartTheme,
+        scale: u32,
+    ) -> Result<(), ChartError>
+    where
+        DB::ErrorType: 'static,
+    {
+        let px = |value: u32| value * scale;
+        let buckets: Vec<Vec<u32>> = VOTE_OPTIONS
+            .iter()
+            .map(|option| Self::bucket_counts(votes, option.action, BAR_BUCKET_MINUTES))
+            .collect();
+
+        // Y軸は最低10票、それを超える区間があれば合わせて広げる
+        let max_count = buckets.iter().flatten().copied().max().unwrap_or(0);
+        let y_max = (max_count as f32 + 2.0).max(10.0);
+
+        let mut chart = Self::build_time_chart(
+            upper,
+            lower,
+            theme.locale.timeline_caption(),
+            theme.locale.y_desc(ChartStyle::Bar),
+            y_max,
+            theme,
+            scale,
+        )?;
+
+        // 1つの区間を選択肢の数で等分して横に並べる
+        let bar_width = BAR_BUCKET_MINUTES as f32 / VOTE_OPTIONS.len() as f32;
+        for (index, ((option, color), counts)) in VOTE_OPTIONS
+            .iter()
+            .zip(theme.series)
+            .zip(buckets)
+            .enumerate()
+        {
+            let style = color.filled();
+            chart
+                .draw_series(
+                    counts
+                        .into_iter()
+                        .enumerate()
+                        .filter(|(_, count)| *count > 0)
+                        .map(|(bucket, count)| {
+                            let x = (bucket * BAR_BUCKET_MINUTES) as f32 + bar_width * index as f32;
+                            Rectangle::new(
+                                [(x + 0.5, 0.0), (x + bar_width - 0.5, count as f32)],
+                                style,
+                            )
+                        }),
+                )?
+                .label(theme.locale.option_label(option))
+                .legend(move |(x, y)| {
+                    Rectangle::new(
+                        [(x, y - px(5) as i32), (x + px(10) as i32, y + px(5) as i32)],
+                        style,
+                    )
+                });
+        }
+
+        Self::draw_legend(&mut chart, theme)
+    }
+
+    /// その時点までの投票に占める各選択肢の割合を積み上げ面グラフで描画する
+    fn draw_stacked_area_timeline<DB: DrawingBackend>(
+        upper: &DrawingArea<DB, Shift>,
+        lower: &DrawingArea<DB, Shift>,
+        votes: &[VoteModel],
+        theme: &ChartTheme,
+        scale: u32,
+    ) -> Result<(), ChartError>
+    where
+        DB::ErrorType: 'static,
+    {
+        let px = |value: u32| value * scale;
+        let cumulative: Vec<Series> = VOTE_OPTIONS
+            .iter()
+            .map(|option| Self::cumulative_series(votes, option.action).0)
+            .collect();
+
+        // 各分の選択肢ごとの割合を下から積み上げた上端の値を求める
+        let chart_minutes = Self::chart_minutes();
+        let mut layers: Vec<Series> = vec![Vec::with_capacity(chart_minutes); cumulative.len()];
+        for minute in 0..chart_minutes {
+            let total: f32 = cumulative.iter().map(|series| series[minute].1).sum();
+            let mut stacked = 0f32;
+            for (layer, series) in layers.iter_mut().zip(&cumulative) {
+                if total > 0.0 {
+                    stacked += series[minute].1 / total * 100.0;
+                }
+                layer.push((minute as f32, stacked));
+            }
+        }
+
+        let mut chart = Self::build_time_chart(
+            upper,
+            lower,
+            theme.locale.timeline_caption(),
+            theme.locale.y_desc(ChartStyle::StackedArea),
+            100.0,
+            theme,
+            scale,
+        )?;
+
+        // 上の層から順に塗り、下の層で重ねて塗ることで積み上げを表現する
+        for ((option, color), layer) in VOTE_OPTIONS.iter().zip(theme.series).zip(layers).rev() {
+            let style = color.mix(0.8).filled();
+            chart
+                .draw_series(
+                    AreaSeries::new(layer, 0.0, style).border_style(color.stroke_width(px(1))),
+                )?
+                .label(theme.locale.option_label(option))
+                .legend(move |(x, y)| {
+                    Rectangle::new(
+                        [(x, y - px(5) as i32), (x + px(10) as i32, y + px(5) as i32)],
+                        style,
+                    )
+                });
+        }
+
+        Self::draw_legend(&mut chart, theme)
+    }
+
+    /// 描画済みのRGBバッファをPNGにエンコードする
+    fn encode_png(buffer: Vec<u8>, width: u32, height: u32) -> Result<Vec<u8>, ChartError> {
+        let image = image::RgbImage::from_raw(width, height, buffer)
+            .ok_or("描画バッファのサイズが画像サイズと一致しません")?;
+
+        let mut png = std::io::Cursor::new(Vec::new());
+        image.write_to(&mut png, image::ImageOutputFormat::Png)?;
+        Ok(png.into_inner())
+    }
+
+    /// グラフのX軸に表示する時間帯を設定する（起動時に一度だけ呼ぶ）
+    pub fn configure(config: &ChartConfig) {
+        let _ = CHART_HOURS.set(config.start_hour..config.end_hour);
+    }
+
+    /// グラフのX軸に表示する時間帯
+    fn chart_hours() -> Range<u32> {
+        CHART_HOURS.get().cloned().unwrap_or(DEFAULT_CHART_HOURS)
+    }
+
+    /// グラフのX軸の分数
+    fn chart_minutes() -> usize {
+        Self::chart_hours().len() * 60
+    }
+
+    /// X軸用に開始時から終了時まで1分間隔の時間ラベルを準備
+    fn time_labels() -> Vec<String> {
         let mut time_labels: Vec<String> = Vec::new();
-        for hour in 14..20 {
+        for hour in Self::chart_hours() {
             for minute in 0..60 {
                 time_labels.push(format!("{:02}:{:02}", hour, minute));
             }
         }
+        time_labels
+    }
 
-        // 時間ごとに投票数を集計
-        let mut hour_counts: HashMap<String, HashMap<String, u32>> = HashMap::new();
+    /// 投票の更新時刻（投票期間のタイムゾーン）がX軸上の何番目の分に当たるかを返す
+    /// 表示範囲外の時刻であれば`None`
+    fn minute_index(vote: &VoteModel) -> Option<usize> {
+        let local_dt = vote
+            .updated_at
+            .with_timezone(&VoteService::period_timezone());
+        let hour = local_dt.hour();
+        let hours = Self::chart_hours();
 
-        for vote in votes {
-            // 日本時間に変換
-            let jst_dt = vote.updated_at.with_timezone(&Tokyo);
-            let hour = jst_dt.hour();
-            let minute = jst_dt.minute();
+        if hours.contains(&hour) {
+            Some(((hour - hours.start) * 60 + local_dt.minute()) as usize)
+        } else {
+            None
+        }
+    }
 
-            // 12時から24時の範囲のみ処理
-            if hour >= 14 && hour < 24 {
-                // 1分単位で集計
-                let time_label = format!("{:02}:{:02}", hour, minute);
-                let action_counts = hour_counts.entry(time_label).or_insert_with(HashMap::new);
-                *action_counts.entry(vote.action).or_insert(0) += 1;
+    /// 指定したアクションの投票数を`bucket_minutes`分ごとの区間に集計する
+    fn bucket_counts(votes: &[VoteModel], action: &str, bucket_minutes: usize) -> Vec<u32> {
+        let mut counts = vec![0u32; Self::chart_minutes().div_ceil(bucket_minutes)];
+        for vote in votes.iter().filter(|vote| vote.action == action) {
+            if let Some(index) = Self::minute_index(vote) {
+                counts[index / bucket_minutes] += 1;
             }
         }
+        counts
+    }
 
-        // 全ての時間ラベルに対してデータが存在するように初期化
-        for hour_label in &time_labels {
-            hour_counts
-                .entry(hour_label.clone())
-                .or_insert_with(HashMap::new);
-        }
+    /// 累積投票数のグラフのY軸の上限
+    /// 最低20票とし、それを超える系列があれば合わせて広げる
+    fn cumulative_y_max<'a>(series: impl Iterator<Item = &'a Series>) -> f32 {
+        let max_count = series
+            .filter_map(|data| data.last().map(|(_, y)| *y))
+            .fold(0f32, f32::max);
+        (max_count + 2.0).max(20.0)
+    }
 
-        // 画像サイズと出力設定
-        let root = BitMapBackend::new(output_path, (1200, 600)).into_drawing_area();
-        root.fill(&WHITE)?;
+    /// 指定したアクションの累積投票数の系列を作成する
+    /// 戻り値は（全ての分の累積値, 値が変わった分の累積値）
+    fn cumulative_series(votes: &[VoteModel], action: &str) -> (Series, Series) {
+        // 1分単位で集計
+        let minute_counts = Self::bucket_counts(votes, action, 1);
 
-        // エリアを分割（上：グラフエリア、下：ラベルエリア）
-        let (upper, lower) = root.split_vertically((94).percent());
+        let mut cumulative_count = 0u32;
+        let mut data_with_changes: Series = Vec::new();
+        let data = minute_counts
+            .iter()
+            .enumerate()
+            .map(|(index, count)| {
+                cumulative_count += count;
 
-        let mut chart = ChartBuilder::on(&upper)
-            .caption("ケバブ屋", ("Noto Sans CJK JP, Liberation Sans, Arial, sans-serif", 40).into_font().color(&BLACK))
-            .margin(20)
-            .x_label_area_size(20)
-            .y_label_area_size(60)
-            .build_cartesian_2d(
-                0f32..time_labels.len() as f32,
-                0f32..20f32, // Y軸を15まで、より適切な目盛り間隔に
-            )?;
+                // 値が変わった時のみdata_with_changesに追加
+                if *count > 0 {
+                    data_with_changes.push((index as f32, cumulative_count as f32));
+                }
+
+                (index as f32, cumulative_count as f32)
+            })
+            .collect();
+
+        (data, data_with_changes)
+    }
+
+    /// 時刻軸のグラフ領域を作成し、メッシュとX軸ラベル・縦線を描画する
+    fn build_time_chart<'a, DB: DrawingBackend>(
+        upper: &'a DrawingArea<DB, Shift>,
+        lower: &DrawingArea<DB, Shift>,
+        caption: &str,
+        y_desc: &str,
+        y_max: f32,
+        theme: &ChartTheme,
+        scale: u32,
+    ) -> Result<TimeChart<'a, DB>, ChartError>
+    where
+        DB::ErrorType: 'static,
+    {
+        let time_labels = Self::time_labels();
+        let mut chart = ChartBuilder::on(upper)
+            .caption(caption, theme.text_style(theme.caption_size))
+            .margin(20 * scale)
+            .x_label_area_size(20 * scale)
+            .y_label_area_size(60 * scale)
+            .build_cartesian_2d(0f32..time_labels.len() as f32, 0f32..y_max)?;
 
         chart
             .configure_mesh()
-            .y_desc("累積投票数")
+            .y_desc(y_desc)
             .y_max_light_lines(5)
             .y_label_formatter(&|y| format!("{}", *y as i32)) // 整数表示
             .y_labels(5)
-            .label_style(("Noto Sans CJK JP, Liberation Sans, Arial, sans-serif", 15).into_font().color(&BLACK))
-            .axis_desc_style(("Noto Sans CJK JP, Liberation Sans, Arial, sans-serif", 20).into_font().color(&BLACK))
+            .axis_style(theme.foreground.stroke_width(scale))
+            .bold_line_style(theme.grid.mix(0.3).stroke_width(scale))
+            .light_line_style(theme.grid.mix(0.1).stroke_width(scale))
+            .label_style(theme.text_style(theme.label_size))
+            .axis_desc_style(theme.text_style(theme.axis_desc_size))
             .x_labels(0)
             .draw()?;
 
         // 手動でX軸ラベルと縦線を描画（30分間隔）
-        for hour in 14..20 {
+        for hour in Self::chart_hours() {
             for minute in [0, 30] {
                 let time_str = format!("{:02}:{:02}", hour, minute);
                 if let Some(index) = time_labels.iter().position(|x| x == &time_str) {
                     let x_pos = index as f32;
 
-                    // ラベルエリアに描画
-                    let chart_width = 1200.0 - 20.0 * 2.0 - 60.0; // 全体幅 - マージン - Y軸ラベルエリア
-                    let x_pixel = 60.0 + (x_pos / time_labels.len() as f32) * chart_width;
-
+                    // グラフ上の座標をピクセル位置に変換してラベルエリアに描画
+                    let (x_pixel, _) = chart.backend_coord(&(x_pos, 0.0));
                     lower.draw(&Text::new(
                         time_str,
-                        (x_pixel as i32, 0),
-                        ("Noto Sans CJK JP, Liberation Sans, Arial, sans-serif", 14).into_font().color(&BLACK),
+                        (x_pixel, 0),
+                        theme.text_style(theme.tick_label_size),
                     ))?;
 
                     // 縦のグリッド線を描画
                     chart.draw_series(std::iter::once(PathElement::new(
-                        vec![(x_pos, 0.0), (x_pos, 20.0)],
-                        RGBColor(128, 128, 128).mix(0.3).stroke_width(1),
+                        vec![(x_pos, 0.0), (x_pos, y_max)],
+                        theme.grid.mix(0.3).stroke_width(scale),
                     )))?;
                 }
             }
         }
 
-        // アクションごとに累積折れ線グラフを描画
-        let actions = ["found", "not_found", "sold_out"];
-        let colors = [&GREEN, &BLUE, &RED];
-        let labels = ["営業してる", "いない", "売り切れた"];
-
-        for (action_idx, action) in actions.iter().enumerate() {
-            let mut cumulative_count = 0u32;
-            let mut data_with_changes: Vec<(f32, f32)> = Vec::new();
-            let data: Vec<(f32, f32)> = time_labels
-                .iter()
-                .enumerate()
-                .map(|(hour_idx, hour_label)| {
-                    let count = hour_counts
-                        .get(hour_label)
-                        .and_then(|counts| counts.get(*action))
-                        .unwrap_or(&0);
-                    let prev_cumulative = cumulative_count;
-                    cumulative_count += count;
-
-                    // 値が変わった時のみdata_with_changesに追加
-                    if *count > 0 {
-                        data_with_changes.push((hour_idx as f32, cumulative_count as f32));
-                    }
-
-                    (hour_idx as f32, cumulative_count as f32)
-                })
-                .collect();
-
-            // 折れ線グラフを描画
-            chart
-                .draw_series(LineSeries::new(data.clone(), colors[action_idx]))?
-                .label(labels[action_idx])
-                .legend(move |(x, y)| {
-                    PathElement::new(vec![(x, y), (x + 10, y)], colors[action_idx])
-                });
-
-            // 値が変わったポイントのみマーク
-            chart.draw_series(
-                data_with_changes
-                    .iter()
-                    .map(|(x, y)| Circle::new((*x, *y), 3, colors[action_idx].filled())),
-            )?;
-        }
+        Ok(chart)
+    }
 
+    /// 凡例を描画する
+    fn draw_legend<'a, DB: DrawingBackend + 'a>(
+        chart: &mut TimeChart<'a, DB>,
+        theme: &ChartTheme,
+    ) -> Result<(), ChartError>
+    where
+        DB::ErrorType: 'static,
+    {
         chart
             .configure_series_labels()
-            .background_style(&WHITE.mix(0.8))
-            .border_style(&BLACK)
-            .label_font(("Noto Sans CJK JP, Liberation Sans, Arial, sans-serif", 15).into_font().color(&BLACK))
+            .background_style(theme.background.mix(0.8))
+            .border_style(theme.foreground)
+            .label_font(theme.text_style(theme.legend_size))
             .draw()?;
-
-        root.present()?;
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::test_support::test_period;
+    use chrono::{NaiveTime, TimeZone};
+
+    /// 投票期間の15時`minute`分（日本時間）に`count`人が`action`に投票したデータ
+    fn votes(action: &str, count: i64, minute: u32) -> Vec<VoteModel> {
+        let voted_at = VoteService::period_timezone()
+            .from_local_datetime(
+                &test_period().and_time(NaiveTime::from_hms_opt(15, minute, 0).unwrap()),
+            )
+            .unwrap()
+            .fixed_offset();
+        (0..count)
+            .map(|user_id| VoteModel {
+                user_id: user_id + i64::from(minute) * 1000,
+                action: action.to_string(),
+                created_at: voted_at,
+                updated_at: voted_at,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn line_chart_axis_grows_with_the_largest_count() {
+        let mut all = votes("found", 30, 0);
+        all.extend(votes("sold_out", 5, 10));
+        let series: Vec<Series> = VOTE_OPTIONS
+            .iter()
+            .map(|option| ChartService::cumulative_series(&all, option.action).0)
+            .collect();
+
+        assert_eq!(ChartService::cumulative_y_max(series.iter()), 32.0);
+        assert_eq!(ChartService::cumulative_y_max(series[2..].iter()), 20.0);
+    }
+
+    #[test]
+    fn every_style_renders_png_and_svg() {
+        let mut all = votes("found", 25, 0);
+        all.extend(votes("not_found", 3, 20));
+        all.extend(votes("sold_out", 2, 40));
+        let theme = ChartTheme::default();
+
+        for style in [ChartStyle::Line, ChartStyle::Bar, ChartStyle::StackedArea] {
+            let content = ChartContent::Timeline { votes: &all, style };
+
+            let png = ChartService::render(&content, &theme, &ChartOutput::png(400, 300, 1))
+                .unwrap_or_else(|e| panic!("{:?}のPNGを描画できません: {}", style, e));
+            assert!(png.starts_with(b"\x89PNG"), "{:?}", style);
+
+            let svg = ChartService::render(&content, &theme, &ChartOutput::svg(400, 300))
+                .unwrap_or_else(|e| panic!("{:?}のSVGを描画できません: {}", style, e));
+            assert!(
+                String::from_utf8(svg).unwrap().contains("<svg"),
+                "{:?}",
+                style
+            );
+        }
+    }
+}
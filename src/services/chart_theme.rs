@@ -0,0 +1,292 @@
+use crate::entities::guild_settings::Model as GuildSettingsModel;
+use crate::services::chart_service::ChartStyle;
+use crate::services::vote_service::VoteOption;
+use chrono::Weekday;
+use font_kit::source::SystemSource;
+use plotters::prelude::*;
+use plotters::style::{RGBColor, TextStyle};
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex, Once};
+use tracing::warn;
+
+/// フォントが見つからない場合に順に試すフォントファミリー
+/// 日本語を表示できるフォントを優先する
+const FONT_FALLBACKS: [&str; 6] = [
+    "Noto Sans CJK JP",
+    "Noto Sans JP",
+    "IPAexGothic",
+    "IPAGothic",
+    "Liberation Sans",
+    "DejaVu Sans",
+];
+
+/// 日本語を表示できるフォントファミリー
+const CJK_FONTS: [&str; 4] = [
+    "Noto Sans CJK JP",
+    "Noto Sans JP",
+    "IPAexGothic",
+    "IPAGothic",
+];
+
+/// フォントファミリーごとのインストール有無のキャッシュ
+static FONT_AVAILABILITY: LazyLock<Mutex<HashMap<String, bool>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+static CJK_FONT_WARNING: Once = Once::new();
+
+/// グラフのラベルに使う言語
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChartLocale {
+    Ja,
+    En,
+}
+
+impl ChartLocale {
+    /// 設定値の文字列から変換（未知の値は`None`）
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "ja" => Some(ChartLocale::Ja),
+            "en" => Some(ChartLocale::En),
+            _ => None,
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            ChartLocale::Ja => "ja",
+            ChartLocale::En => "en",
+        }
+    }
+
+    /// タイムライングラフのタイトル
+    pub fn timeline_caption(&self) -> &'static str {
+        match self {
+            ChartLocale::Ja => "ケバブ屋",
+            ChartLocale::En => "Kebab stand",
+        }
+    }
+
+    /// 期間比較グラフのタイトル
+    pub fn comparison_caption(&self, option: &VoteOption) -> String {
+        match self {
+            ChartLocale::Ja => format!("ケバブ屋「{}」の期間比較", option.label),
+            ChartLocale::En => format!("Kebab stand \"{}\" by period", option.label_en),
+        }
+    }
+
+    /// グラフの描画スタイルに合わせたY軸の説明
+    pub fn y_desc(&self, style: ChartStyle) -> &'static str {
+        match (self, style) {
+            (ChartLocale::Ja, ChartStyle::Line) => "累積投票数",
+            (ChartLocale::Ja, ChartStyle::Bar) => "15分ごとの投票数",
+            (ChartLocale::Ja, ChartStyle::StackedArea) => "投票の割合 (%)",
+            (ChartLocale::En, ChartStyle::Line) => "Cumulative votes",
+            (ChartLocale::En, ChartStyle::Bar) => "Votes per 15 min",
+            (ChartLocale::En, ChartStyle::StackedArea) => "Share of votes (%)",
+        }
+    }
+
+    /// 凡例に表示する投票の選択肢の名前
+    pub fn option_label(&self, option: &VoteOption) -> &'static str {
+        match self {
+            ChartLocale::Ja => option.label,
+            ChartLocale::En => option.label_en,
+        }
+    }
+
+    /// 曜日の短い表記
+    pub fn weekday(&self, weekday: Weekday) -> &'static str {
+        match self {
+            ChartLocale::Ja => crate::services::BoardUIService::get_weekday_string(weekday),
+            ChartLocale::En => match weekday {
+                Weekday::Mon => "Mon",
+                Weekday::Tue => "Tue",
+                Weekday::Wed => "Wed",
+                Weekday::Thu => "Thu",
+                Weekday::Fri => "Fri",
+                Weekday::Sat => "Sat",
+                Weekday::Sun => "Sun",
+            },
+        }
+    }
+}
+
+/// グラフの配色・フォント・文字サイズ
+#[derive(Debug, Clone)]
+pub struct ChartTheme {
+    pub dark_mode: bool,
+    pub background: RGBColor,
+    pub foreground: RGBColor,
+    pub grid: RGBColor,
+    /// `VOTE_OPTIONS`と同じ順番の系列の色
+    pub series: [RGBColor; 3],
+    pub font_family: String,
+    pub caption_size: u32,
+    pub axis_desc_size: u32,
+    pub label_size: u32,
+    pub tick_label_size: u32,
+    pub legend_size: u32,
+    pub locale: ChartLocale,
+}
+
+impl Default for ChartTheme {
+    fn default() -> Self {
+        Self::light()
+    }
+}
+
+impl ChartTheme {
+    /// 白背景のテーマ
+    pub fn light() -> Self {
+        Self {
+            dark_mode: false,
+            background: WHITE,
+            foreground: BLACK,
+            grid: RGBColor(128, 128, 128),
+            series: [GREEN, BLUE, RED],
+            font_family: Self::resolve_font_family(None),
+            caption_size: 40,
+            axis_desc_size: 20,
+            label_size: 15,
+            tick_label_size: 14,
+            legend_size: 15,
+            locale: ChartLocale::Ja,
+        }
+    }
+
+    /// Discordのダークテーマに馴染む暗い背景のテーマ
+    pub fn dark() -> Self {
+        Self {
+            dark_mode: true,
+            background: RGBColor(49, 51, 56),
+            foreground: RGBColor(219, 222, 225),
+            grid: RGBColor(148, 155, 164),
+            series: [
+                RGBColor(87, 242, 135),
+                RGBColor(88, 101, 242),
+                RGBColor(237, 66, 69),
+            ],
+            ..Self::light()
+        }
+    }
+
+    /// サーバー設定からテーマを組み立てる（設定がなければ既定のテーマ）
+    pub fn from_settings(settings: Option<&GuildSettingsModel>) -> Self {
+        let Some(settings) = settings else {
+            return Self::default();
+        };
+
+        let mut theme = if settings.chart_theme == "dark" {
+            Self::dark()
+        } else {
+            Self::light()
+        };
+
+        theme.locale = ChartLocale::from_code(&settings.chart_locale).unwrap_or(ChartLocale::Ja);
+
+        if let Some(font_family) = &settings.chart_font_family {
+            theme.font_family = Self::resolve_font_family(Some(font_family));
+        }
+        if let Some(scale) = settings.chart_font_scale {
+            theme = theme.with_font_scale(scale);
+        }
+        if let Some(background) = settings
+            .chart_background
+            .as_deref()
+            .and_then(Self::parse_hex_color)
+        {
+            theme.background = background;
+        }
+        if let Some(series) = settings
+            .chart_series_colors
+            .as_deref()
+            .and_then(Self::parse_series_colors)
+        {
+            theme.series = series;
+        }
+
+        theme
+    }
+
+    /// 文字サイズを一律に拡大・縮小する
+    pub fn with_font_scale(mut self, scale: f64) -> Self {
+        let scaled = |size: u32| ((size as f64 * scale).round() as u32).max(1);
+        self.caption_size = scaled(self.caption_size);
+        self.axis_desc_size = scaled(self.axis_desc_size);
+        self.label_size = scaled(self.label_size);
+        self.tick_label_size = scaled(self.tick_label_size);
+        self.legend_size = scaled(self.legend_size);
+        self
+    }
+
+    /// 期間比較グラフで`index`番目の期間に使う色
+    /// ダークモードでは暗い背景でも見えるよう白に寄せる
+    pub fn period_color(&self, index: usize) -> RGBColor {
+        let RGBAColor(r, g, b, _) = Palette99::pick(index).to_rgba();
+        if self.dark_mode {
+            let lighten = |channel: u8| channel + ((255 - channel as u16) * 2 / 5) as u8;
+            RGBColor(lighten(r), lighten(g), lighten(b))
+        } else {
+            RGBColor(r, g, b)
+        }
+    }
+
+    /// テーマのフォントと文字色で指定サイズのテキストスタイルを作成
+    pub fn text_style(&self, size: u32) -> TextStyle<'_> {
+        (self.font_family.as_str(), size)
+            .into_font()
+            .color(&self.foreground)
+    }
+
+    /// `#RRGGBB`形式の色を解析
+    pub fn parse_hex_color(value: &str) -> Option<RGBColor> {
+        let hex = value.trim().strip_prefix('#')?;
+        if hex.len() != 6 {
+            return None;
+        }
+        let channel = |range| u8::from_str_radix(hex.get(range)?, 16).ok();
+        Some(RGBColor(channel(0..2)?, channel(2..4)?, channel(4..6)?))
+    }
+
+    /// カンマ区切りの`#RRGGBB`を系列の色として解析（選択肢の数と一致する必要がある）
+    pub fn parse_series_colors(value: &str) -> Option<[RGBColor; 3]> {
+        let colors: Vec<RGBColor> = value
+            .split(',')
+            .map(Self::parse_hex_color)
+            .collect::<Option<_>>()?;
+        colors.try_into().ok()
+    }
+
+    /// 使用するフォントファミリーを決定する
+    /// 指定されたフォント、既定のフォールバック候補の順にインストール済みのものを探す
+    pub fn resolve_font_family(preferred: Option<&str>) -> String {
+        let candidates = preferred.into_iter().chain(FONT_FALLBACKS);
+
+        let resolved = candidates
+            .into_iter()
+            .find(|family| Self::is_font_available(family));
+
+        if !CJK_FONTS
+            .iter()
+            .any(|family| Self::is_font_available(family))
+        {
+            CJK_FONT_WARNING.call_once(|| {
+                warn!(
+                    candidates = %CJK_FONTS.join(", "),
+                    "日本語フォントが見つかりません。グラフの日本語が正しく表示されない可能性があります"
+                );
+            });
+        }
+
+        // 何も見つからなければplottersの既定フォントに任せる
+        resolved.unwrap_or("sans-serif").to_string()
+    }
+
+    /// フォントファミリーがシステムにインストールされているか確認する
+    pub fn is_font_available(family: &str) -> bool {
+        let mut availability = FONT_AVAILABILITY.lock().unwrap_or_else(|e| e.into_inner());
+        *availability
+            .entry(family.to_string())
+            .or_insert_with(|| SystemSource::new().select_family_by_name(family).is_ok())
+    }
+}
@@ -0,0 +1,392 @@
+use crate::Error;
+use crate::entities::board_data::Model as BoardDataModel;
+use crate::entities::vote_history;
+use crate::services::board_service::BoardService;
+use crate::services::vote_service::{VOTE_OPTIONS, VoteService};
+use chrono::{DateTime, FixedOffset, NaiveDate};
+use sea_orm::DatabaseConnection;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use tracing::instrument;
+
+/// エクスポートするデータの種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportKind {
+    /// 投票履歴（投票の変更を全て含む）
+    Votes,
+    /// 掲示板の一覧
+    Boards,
+    /// 期間ごとの最終的な投票数の集計
+    Summaries,
+}
+
+impl ExportKind {
+    pub fn code(&self) -> &'static str {
+        match self {
+            ExportKind::Votes => "votes",
+            ExportKind::Boards => "boards",
+            ExportKind::Summaries => "summaries",
+        }
+    }
+}
+
+/// エクスポートの出力形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+impl ExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+        }
+    }
+}
+
+/// エクスポートする投票期間の範囲
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportRange {
+    /// 現在の投票期間
+    CurrentPeriod,
+    /// 開始日から終了日までの投票期間（両端を含む）
+    Periods { start: NaiveDate, end: NaiveDate },
+}
+
+impl ExportRange {
+    /// 範囲の最初と最後の期間の日付
+    pub fn bounds(&self) -> (NaiveDate, NaiveDate) {
+        match self {
+            ExportRange::CurrentPeriod => {
+                let current = VoteService::get_current_jst_afternoon_period();
+                (current, current)
+            }
+            ExportRange::Periods { start, end } => (*start, *end),
+        }
+    }
+}
+
+/// エクスポートの指定内容
+#[derive(Debug, Clone, Copy)]
+pub struct ExportRequest {
+    pub kind: ExportKind,
+    pub format: ExportFormat,
+    /// 掲示板の一覧では使用しない
+    pub range: ExportRange,
+    /// 指定した場合はそのサーバーのデータのみを出力する
+    pub guild_id: Option<i64>,
+}
+
+/// エクスポートした結果のファイル
+pub struct ExportFile {
+    pub filename: String,
+    pub content: Vec<u8>,
+    /// 出力したデータの件数
+    pub rows: usize,
+}
+
+/// 投票履歴の1件
+#[derive(Debug, Clone, Serialize)]
+pub struct VoteRecord {
+    pub period_date: NaiveDate,
+    pub user_id: i64,
+    pub guild_id: Option<i64>,
+    pub action: String,
+    pub voted_at: DateTime<FixedOffset>,
+}
+
+/// 掲示板の1件
+#[derive(Debug, Clone, Serialize)]
+pub struct BoardRecord {
+    pub id: i32,
+    pub server_id: i64,
+    pub channel_id: i64,
+    pub message_id: i64,
+    pub chart_style: String,
+    pub created_at: DateTime<FixedOffset>,
+    pub updated_at: DateTime<FixedOffset>,
+}
+
+impl From<BoardDataModel> for BoardRecord {
+    fn from(board: BoardDataModel) -> Self {
+        Self {
+            id: board.id,
+            server_id: board.server_id,
+            channel_id: board.channel_id,
+            message_id: board.message_id,
+            chart_style: board.chart_style,
+            created_at: board.created_at,
+            updated_at: board.updated_at,
+        }
+    }
+}
+
+/// 1つの投票期間の集計
+#[derive(Debug, Clone, Serialize)]
+pub struct PeriodSummary {
+    pub period_date: NaiveDate,
+    /// 選択肢ごとの最終的な投票数
+    pub counts: BTreeMap<String, u64>,
+    pub total: u64,
+}
+
+pub struct ExportService;
+
+impl ExportService {
+    /// 指定内容でデータをエクスポートし、ファイルの内容を返す
+    #[instrument(skip(db))]
+    pub async fn export(
+        db: &DatabaseConnection,
+        request: &ExportRequest,
+    ) -> Result<ExportFile, Error> {
+        let (content, rows) = match request.kind {
+            ExportKind::Votes => {
+                let records = Self::get_vote_records(db, request.range, request.guild_id).await?;
+                let content = match request.format {
+                    ExportFormat::Csv => Self::votes_to_csv(&records),
+                    ExportFormat::Json => serde_json::to_vec_pretty(&records)?,
+                };
+                (content, records.len())
+            }
+            ExportKind::Boards => {
+                let records = Self::get_board_records(db, request.guild_id).await?;
+                let content = match request.format {
+                    ExportFormat::Csv => Self::boards_to_csv(&records),
+                    ExportFormat::Json => serde_json::to_vec_pretty(&records)?,
+                };
+                (content, records.len())
+            }
+            ExportKind::Summaries => {
+                let summaries =
+                    Self::get_period_summaries(db, request.range, request.guild_id).await?;
+                let content = match request.format {
+                    ExportFormat::Csv => Self::summaries_to_csv(&summaries),
+                    ExportFormat::Json => serde_json::to_vec_pretty(&summaries)?,
+                };
+                (content, summaries.len())
+            }
+        };
+
+        Ok(ExportFile {
+            filename: Self::filename(request),
+            content,
+            rows,
+        })
+    }
+
+    /// 指定範囲の投票履歴を取得
+    pub async fn get_vote_records(
+        db: &DatabaseConnection,
+        range: ExportRange,
+        guild_id: Option<i64>,
+    ) -> Result<Vec<VoteRecord>, Error> {
+        let (start, end) = range.bounds();
+        let history = VoteService::get_vote_history(db, start, end, guild_id).await?;
+
+        Ok(history
+            .into_iter()
+            .map(|entry| VoteRecord {
+                period_date: entry.period_date,
+                user_id: entry.user_id,
+                guild_id: entry.guild_id,
+                action: entry.action,
+                voted_at: entry.created_at,
+            })
+            .collect())
+    }
+
+    /// 掲示板の一覧を取得
+    pub async fn get_board_records(
+        db: &DatabaseConnection,
+        guild_id: Option<i64>,
+    ) -> Result<Vec<BoardRecord>, Error> {
+        let boards = match guild_id {
+            Some(guild_id) => BoardService::get_board_data_by_server_id(db, guild_id).await?,
+            None => BoardService::get_all_board_data(db).await?,
+        };
+        Ok(boards.into_iter().map(BoardRecord::from).collect())
+    }
+
+    /// 指定範囲の期間ごとに、ユーザーごとの最後の投票を集計する
+    /// 投票のない期間は含まない
+    pub async fn get_period_summaries(
+        db: &DatabaseConnection,
+        range: ExportRange,
+        guild_id: Option<i64>,
+    ) -> Result<Vec<PeriodSummary>, Error> {
+        let (start, end) = range.bounds();
+        let history = VoteService::get_vote_history(db, start, end, guild_id).await?;
+        Ok(Self::summarize_periods(history))
+    }
+
+    /// 古い順に並んだ投票履歴を期間ごとに分け、最終的な投票を選択肢ごとに数える
+    /// 最終的な投票は`/vote_chart`や統計と同じく`VoteService::final_votes_from_history`で求める
+    fn summarize_periods(history: Vec<vote_history::Model>) -> Vec<PeriodSummary> {
+        let mut periods: BTreeMap<NaiveDate, Vec<vote_history::Model>> = BTreeMap::new();
+        for entry in history {
+            periods.entry(entry.period_date).or_default().push(entry);
+        }
+
+        periods
+            .into_iter()
+            .map(|(period_date, entries)| {
+                let mut counts: BTreeMap<String, u64> = VOTE_OPTIONS
+                    .iter()
+                    .map(|option| (option.action.to_string(), 0))
+                    .collect();
+                for vote in VoteService::final_votes_from_history(entries) {
+                    *counts.entry(vote.action).or_default() += 1;
+                }
+                let total = counts.values().sum();
+                PeriodSummary {
+                    period_date,
+                    counts,
+                    total,
+                }
+            })
+            .collect()
+    }
+
+    /// エクスポートファイルの名前（例: `votes_2025-06-01_2025-06-07.csv`）
+    fn filename(request: &ExportRequest) -> String {
+        let stem = match request.kind {
+            ExportKind::Boards => request.kind.code().to_string(),
+            _ => {
+                let (start, end) = request.range.bounds();
+                if start == end {
+                    format!("{}_{}", request.kind.code(), start)
+                } else {
+                    format!("{}_{}_{}", request.kind.code(), start, end)
+                }
+            }
+        };
+        format!("{}.{}", stem, request.format.extension())
+    }
+
+    pub(crate) fn votes_to_csv(records: &[VoteRecord]) -> Vec<u8> {
+        Self::to_csv(
+            &["period_date", "user_id", "guild_id", "action", "voted_at"],
+            records.iter().map(|record| {
+                vec![
+                    record.period_date.to_string(),
+                    record.user_id.to_string(),
+                    record.guild_id.map(|id| id.to_string()).unwrap_or_default(),
+                    record.action.clone(),
+                    record.voted_at.to_rfc3339(),
+                ]
+            }),
+        )
+    }
+
+    fn boards_to_csv(records: &[BoardRecord]) -> Vec<u8> {
+        Self::to_csv(
+            &[
+                "id",
+                "server_id",
+                "channel_id",
+                "message_id",
+                "chart_style",
+                "created_at",
+                "updated_at",
+            ],
+            records.iter().map(|record| {
+                vec![
+                    record.id.to_string(),
+                    record.server_id.to_string(),
+                    record.channel_id.to_string(),
+                    record.message_id.to_string(),
+                    record.chart_style.clone(),
+                    record.created_at.to_rfc3339(),
+                    record.updated_at.to_rfc3339(),
+                ]
+            }),
+        )
+    }
+
+    /// 選択肢ごとの投票数を列にした集計のCSV
+    /// 選択肢にない投票が含まれていればその列も追加する
+    fn summaries_to_csv(summaries: &[PeriodSummary]) -> Vec<u8> {
+        let mut actions: Vec<&str> = VOTE_OPTIONS.iter().map(|option| option.action).collect();
+        for summary in summaries {
+            for action in summary.counts.keys() {
+                if !actions.contains(&action.as_str()) {
+                    actions.push(action);
+                }
+            }
+        }
+
+        let mut header = vec!["period_date"];
+        header.extend(&actions);
+        header.push("total");
+
+        Self::to_csv(
+            &header,
+            summaries.iter().map(|summary| {
+                let mut row = vec![summary.period_date.to_string()];
+                row.extend(actions.iter().map(|action| {
+                    summary
+                        .counts
+                        .get(*action)
+                        .copied()
+                        .unwrap_or(0)
+                        .to_string()
+                }));
+                row.push(summary.total.to_string());
+                row
+            }),
+        )
+    }
+
+    /// ヘッダーと各行からCSVを作成する
+    fn to_csv(header: &[&str], rows: impl Iterator<Item = Vec<String>>) -> Vec<u8> {
+        let mut csv = header.join(",");
+        csv.push('\n');
+        for row in rows {
+            let fields: Vec<String> = row.iter().map(|field| Self::csv_field(field)).collect();
+            csv.push_str(&fields.join(","));
+            csv.push('\n');
+        }
+        csv.into_bytes()
+    }
+
+    /// カンマ・改行・ダブルクォートを含む値をダブルクォートで囲む
+    fn csv_field(value: &str) -> String {
+        if value.contains([',', '"', '\n', '\r']) {
+            format!("\"{}\"", value.replace('"', "\"\""))
+        } else {
+            value.to_string()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::import_service::UNKNOWN_REPORTER_ID;
+    use crate::services::test_support::{history, history_after, test_period};
+    use crate::services::vote_service::RETRACT_ACTION;
+    use chrono::Days;
+
+    #[test]
+    fn summaries_count_each_anonymous_row_in_a_period() {
+        let summaries = ExportService::summarize_periods(vec![
+            history(1, UNKNOWN_REPORTER_ID, "found", 0),
+            history(2, UNKNOWN_REPORTER_ID, "found", 5),
+            history(3, 42, "not_found", 10),
+            history(4, 42, "sold_out", 15),
+            history_after(1, 5, 42, "found", 0),
+            history_after(1, 6, 42, RETRACT_ACTION, 5),
+        ]);
+
+        assert_eq!(summaries.len(), 2);
+        assert_eq!(summaries[0].period_date, test_period());
+        assert_eq!(summaries[0].counts["found"], 2);
+        assert_eq!(summaries[0].counts["not_found"], 0);
+        assert_eq!(summaries[0].counts["sold_out"], 1);
+        assert_eq!(summaries[0].total, 3);
+        assert_eq!(summaries[1].period_date, test_period() + Days::new(1));
+        assert_eq!(summaries[1].total, 0);
+    }
+}
@@ -0,0 +1,203 @@
+use crate::entities::guild_settings::{self, Model as GuildSettingsModel};
+use crate::entities::prelude::*;
+use crate::services::chart_theme::ChartTheme;
+use crate::services::voter_eligibility::VoteEligibility;
+use chrono::Utc;
+use sea_orm::*;
+
+pub struct GuildSettingsService;
+
+/// グラフテーマ設定の変更内容（`None`の項目は変更しない）
+#[derive(Debug, Default)]
+pub struct ChartThemeUpdate {
+    pub theme: Option<String>,
+    pub locale: Option<String>,
+    pub font_family: Option<String>,
+    pub font_scale: Option<f64>,
+    pub background: Option<String>,
+    pub series_colors: Option<String>,
+}
+
+impl GuildSettingsService {
+    /// サーバーIDでサーバー設定を取得
+    pub async fn get_guild_settings(
+        db: &DatabaseConnection,
+        server_id: i64,
+    ) -> Result<Option<GuildSettingsModel>, DbErr> {
+        GuildSettings::find_by_id(server_id).one(db).await
+    }
+
+    /// サーバーのグラフテーマを取得（DMなどサーバー外では既定のテーマ）
+    pub async fn get_chart_theme(
+        db: &DatabaseConnection,
+        server_id: Option<i64>,
+    ) -> Result<ChartTheme, DbErr> {
+        let settings = match server_id {
+            Some(server_id) => Self::get_guild_settings(db, server_id).await?,
+            None => None,
+        };
+        Ok(ChartTheme::from_settings(settings.as_ref()))
+    }
+
+    /// サーバーの投票できるユーザーの条件を取得（DMなどサーバー外では条件なし）
+    pub async fn get_vote_eligibility(
+        db: &DatabaseConnection,
+        server_id: Option<i64>,
+    ) -> Result<VoteEligibility, DbErr> {
+        let settings = match server_id {
+            Some(server_id) => Self::get_guild_settings(db, server_id).await?,
+            None => None,
+        };
+        Ok(VoteEligibility::from_settings(settings.as_ref()))
+    }
+
+    /// サーバー設定を取得し、なければ既定値で作成する
+    pub async fn get_or_create_guild_settings(
+        db: &DatabaseConnection,
+        server_id: i64,
+    ) -> Result<GuildSettingsModel, DbErr> {
+        if let Some(settings) = Self::get_guild_settings(db, server_id).await? {
+            return Ok(settings);
+        }
+
+        let now = Utc::now().into();
+        let settings = guild_settings::ActiveModel {
+            server_id: Set(server_id),
+            chart_theme: Set("light".to_string()),
+            chart_locale: Set("ja".to_string()),
+            chart_font_family: Set(None),
+            chart_font_scale: Set(None),
+            chart_background: Set(None),
+            chart_series_colors: Set(None),
+            vote_required_role_id: Set(None),
+            vote_min_account_age_days: Set(None),
+            vote_min_membership_days: Set(None),
+            audit_log_channel_id: Set(None),
+            reputation_weighting: Set(false),
+            created_at: Set(now),
+            updated_at: Set(now),
+        };
+
+        settings.insert(db).await
+    }
+
+    /// グラフテーマ設定を更新
+    pub async fn update_chart_theme(
+        db: &DatabaseConnection,
+        server_id: i64,
+        update: ChartThemeUpdate,
+    ) -> Result<GuildSettingsModel, DbErr> {
+        let settings = Self::get_or_create_guild_settings(db, server_id).await?;
+
+        let mut settings: guild_settings::ActiveModel = settings.into();
+        if let Some(theme) = update.theme {
+            settings.chart_theme = Set(theme);
+        }
+        if let Some(locale) = update.locale {
+            settings.chart_locale = Set(locale);
+        }
+        if let Some(font_family) = update.font_family {
+            settings.chart_font_family = Set(Some(font_family));
+        }
+        if let Some(font_scale) = update.font_scale {
+            settings.chart_font_scale = Set(Some(font_scale));
+        }
+        if let Some(background) = update.background {
+            settings.chart_background = Set(Some(background));
+        }
+        if let Some(series_colors) = update.series_colors {
+            settings.chart_series_colors = Set(Some(series_colors));
+        }
+        settings.updated_at = Set(Utc::now().into());
+
+        settings.update(db).await
+    }
+
+    /// グラフテーマ設定を既定値に戻す
+    pub async fn reset_chart_theme(
+        db: &DatabaseConnection,
+        server_id: i64,
+    ) -> Result<GuildSettingsModel, DbErr> {
+        let settings = Self::get_or_create_guild_settings(db, server_id).await?;
+
+        let mut settings: guild_settings::ActiveModel = settings.into();
+        settings.chart_theme = Set("light".to_string());
+        settings.chart_locale = Set("ja".to_string());
+        settings.chart_font_family = Set(None);
+        settings.chart_font_scale = Set(None);
+        settings.chart_background = Set(None);
+        settings.chart_series_colors = Set(None);
+        settings.updated_at = Set(Utc::now().into());
+
+        settings.update(db).await
+    }
+
+    /// 投票できるユーザーの条件を保存（全ての条件を置き換える）
+    pub async fn update_vote_eligibility(
+        db: &DatabaseConnection,
+        server_id: i64,
+        eligibility: VoteEligibility,
+    ) -> Result<GuildSettingsModel, DbErr> {
+        let settings = Self::get_or_create_guild_settings(db, server_id).await?;
+
+        let mut settings: guild_settings::ActiveModel = settings.into();
+        settings.vote_required_role_id = Set(eligibility.required_role_id);
+        settings.vote_min_account_age_days =
+            Set(eligibility.min_account_age_days.map(|days| days as i32));
+        settings.vote_min_membership_days =
+            Set(eligibility.min_membership_days.map(|days| days as i32));
+        settings.updated_at = Set(Utc::now().into());
+
+        settings.update(db).await
+    }
+
+    /// 監査ログを投稿するチャンネルを取得（未設定なら`None`）
+    pub async fn get_audit_log_channel(
+        db: &DatabaseConnection,
+        server_id: i64,
+    ) -> Result<Option<i64>, DbErr> {
+        Ok(Self::get_guild_settings(db, server_id)
+            .await?
+            .and_then(|settings| settings.audit_log_channel_id))
+    }
+
+    /// 監査ログを投稿するチャンネルを保存（`None`なら監査ログを無効にする）
+    pub async fn update_audit_log_channel(
+        db: &DatabaseConnection,
+        server_id: i64,
+        channel_id: Option<i64>,
+    ) -> Result<GuildSettingsModel, DbErr> {
+        let settings = Self::get_or_create_guild_settings(db, server_id).await?;
+
+        let mut settings: guild_settings::ActiveModel = settings.into();
+        settings.audit_log_channel_id = Set(channel_id);
+        settings.updated_at = Set(Utc::now().into());
+
+        settings.update(db).await
+    }
+
+    /// 信頼度による票の重み付けが有効かどうかを取得（設定がなければ無効）
+    pub async fn get_reputation_weighting(
+        db: &DatabaseConnection,
+        server_id: i64,
+    ) -> Result<bool, DbErr> {
+        Ok(Self::get_guild_settings(db, server_id)
+            .await?
+            .is_some_and(|settings| settings.reputation_weighting))
+    }
+
+    /// 信頼度による票の重み付けの有効・無効を保存
+    pub async fn update_reputation_weighting(
+        db: &DatabaseConnection,
+        server_id: i64,
+        enabled: bool,
+    ) -> Result<GuildSettingsModel, DbErr> {
+        let settings = Self::get_or_create_guild_settings(db, server_id).await?;
+
+        let mut settings: guild_settings::ActiveModel = settings.into();
+        settings.reputation_weighting = Set(enabled);
+        settings.updated_at = Set(Utc::now().into());
+
+        settings.update(db).await
+    }
+}
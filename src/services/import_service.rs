@@ -0,0 +1,409 @@
+use crate::Error;
+use crate::entities::vote_history;
+use crate::services::vote_service::{RETRACT_ACTION, VOTE_OPTIONS, VoteService};
+use chrono::{DateTime, NaiveDate, NaiveTime, TimeZone, Utc};
+use sea_orm::*;
+use std::collections::HashMap;
+use tracing::{info, instrument};
+
+/// 記録者が分からない行に割り当てるユーザーID
+pub const UNKNOWN_REPORTER_ID: i64 = 0;
+
+/// 取り込みに失敗した行
+#[derive(Debug, Clone)]
+pub struct ImportRowError {
+    /// ヘッダーを1行目とした行番号
+    pub line: usize,
+    pub message: String,
+}
+
+/// 取り込み結果
+#[derive(Debug, Clone, Default)]
+pub struct ImportReport {
+    /// 新しく投票履歴に追加した行数
+    pub imported: usize,
+    /// 既に取り込まれていたため飛ばした行数
+    pub skipped: usize,
+    pub errors: Vec<ImportRowError>,
+}
+
+/// 検証済みの1行
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct ImportRow {
+    user_id: i64,
+    action: String,
+    voted_at: DateTime<Utc>,
+}
+
+/// CSVを検証した結果
+/// 同じ内容の行はファイル内の出現回数と一緒にまとめる
+#[derive(Debug, Default)]
+struct ParsedCsv {
+    rows: Vec<(ImportRow, u64)>,
+    errors: Vec<ImportRowError>,
+}
+
+/// CSVの列の位置
+struct Columns {
+    date: Option<usize>,
+    time: Option<usize>,
+    voted_at: Option<usize>,
+    action: usize,
+    user_id: Option<usize>,
+}
+
+pub struct ImportService;
+
+impl ImportService {
+    /// 表計算ソフトで記録していた履歴のCSVを投票履歴に取り込む
+    ///
+    /// 1行目はヘッダーで、`date`（YYYY-MM-DD または YYYY/MM/DD）と`time`（投票期間のタイムゾーンでの HH:MM、既定は日本時間）、
+    /// または`/export`が書き出す`voted_at`（RFC 3339）の列と、
+    /// `action`（found / not_found / sold_out または選択肢の表示名）の列が必須、
+    /// `user_id`の列は任意（空欄か0の場合は記録者不明として扱う）。
+    /// 同じ内容の行は既に取り込まれている件数を超えた分だけ追加するため、同じファイルを何度取り込んでもよい。
+    /// 不正な行は取り込まずに行番号と理由を結果に含める。
+    #[instrument(skip(db, content))]
+    pub async fn import_vote_history_csv(
+        db: &DatabaseConnection,
+        content: &str,
+        guild_id: Option<i64>,
+    ) -> Result<ImportReport, Error> {
+        let parsed = Self::parse_history_csv(content).map_err(Error::InvalidInput)?;
+        let mut report = ImportReport {
+            errors: parsed.errors,
+            ..Default::default()
+        };
+
+        // 全ての行をまとめて取り込む
+        // 記録者不明の行は同じ内容でも別の投票なので、件数で比べて足りない分を追加する
+        let txn = db.begin().await?;
+        for (row, count) in parsed.rows {
+            let existing = Self::count_existing(&txn, &row, guild_id).await?;
+            let missing = count.saturating_sub(existing);
+            report.skipped += (count - missing) as usize;
+
+            for _ in 0..missing {
+                vote_history::ActiveModel {
+                    user_id: Set(row.user_id),
+                    guild_id: Set(guild_id),
+                    action: Set(row.action.clone()),
+                    period_date: Set(VoteService::get_jst_afternoon_period(row.voted_at)),
+                    created_at: Set(VoteService::to_db_timestamp(row.voted_at)),
+                    ..Default::default()
+                }
+                .insert(&txn)
+                .await?;
+                report.imported += 1;
+            }
+        }
+        txn.commit().await?;
+        info!(
+            imported = report.imported,
+            skipped = report.skipped,
+            errors = report.errors.len(),
+            "投票履歴を取り込みました"
+        );
+
+        Ok(report)
+    }
+
+    /// 同じ内容の投票履歴が既に何件あるか
+    async fn count_existing(
+        txn: &DatabaseTransaction,
+        row: &ImportRow,
+        guild_id: Option<i64>,
+    ) -> Result<u64, DbErr> {
+        let guild_condition = match guild_id {
+            Some(guild_id) => vote_history::Column::GuildId.eq(guild_id),
+            None => vote_history::Column::GuildId.is_null(),
+        };
+
+        let voted_at = VoteService::to_db_timestamp(row.voted_at);
+        let period = VoteService::get_jst_afternoon_period(row.voted_at);
+        vote_history::Entity::find()
+            .filter(vote_history::Column::UserId.eq(row.user_id))
+            .filter(vote_history::Column::Action.eq(row.action.as_str()))
+            .filter(vote_history::Column::PeriodDate.eq(period))
+            .filter(vote_history::Column::CreatedAt.eq(voted_at))
+            .filter(guild_condition)
+            .count(txn)
+            .await
+    }
+
+    /// CSVを読み取って検証し、同じ内容の行をまとめる
+    fn parse_history_csv(content: &str) -> Result<ParsedCsv, String> {
+        // 表計算ソフトが付けるBOMを取り除く
+        let content = content.trim_start_matches('\u{feff}');
+        let mut records = Self::parse_csv(content).into_iter();
+
+        let Some((_, header)) = records.next() else {
+            return Err("CSVが空です。".to_string());
+        };
+        let columns = Self::find_columns(&header)?;
+
+        let mut parsed = ParsedCsv::default();
+        let mut positions: HashMap<ImportRow, usize> = HashMap::new();
+        for (line, record) in records {
+            // 空行は無視する
+            if record.iter().all(|field| field.trim().is_empty()) {
+                continue;
+            }
+            match Self::parse_row(&record, &columns) {
+                Ok(row) => match positions.get(&row) {
+                    Some(&index) => parsed.rows[index].1 += 1,
+                    None => {
+                        positions.insert(row.clone(), parsed.rows.len());
+                        parsed.rows.push((row, 1));
+                    }
+                },
+                Err(message) => parsed.errors.push(ImportRowError { line, message }),
+            }
+        }
+
+        Ok(parsed)
+    }
+
+    /// ヘッダーから必要な列の位置を探す
+    /// 日時は`date`と`time`の組か、`/export`が書き出す`voted_at`のどちらかがあればよい
+    fn find_columns(header: &[String]) -> Result<Columns, String> {
+        let position = |name: &str| {
+            header
+                .iter()
+                .position(|column| column.trim().eq_ignore_ascii_case(name))
+        };
+
+        let voted_at = position("voted_at");
+        let required: &[&str] = if voted_at.is_some() {
+            &["action"]
+        } else {
+            &["date", "time", "action"]
+        };
+        let missing: Vec<&str> = required
+            .iter()
+            .copied()
+            .filter(|name| position(name).is_none())
+            .collect();
+        if !missing.is_empty() {
+            return Err(format!(
+                "ヘッダーに必須の列がありません: {}",
+                missing.join(", ")
+            ));
+        }
+
+        Ok(Columns {
+            date: position("date"),
+            time: position("time"),
+            voted_at,
+            action: position("action").unwrap_or_default(),
+            user_id: position("user_id"),
+        })
+    }
+
+    /// 1行を検証して取り込む内容に変換する
+    fn parse_row(record: &[String], columns: &Columns) -> Result<ImportRow, String> {
+        let field = |index: usize| record.get(index).map(|value| value.trim()).unwrap_or("");
+
+        let action = field(columns.action);
+        let action = Self::parse_action(action)
+            .ok_or_else(|| format!("投票の種類「{}」は選択肢にありません", action))?;
+
+        let user_id = match columns.user_id.map(field) {
+            None | Some("") => UNKNOWN_REPORTER_ID,
+            Some(user_id) => user_id
+                .parse::<i64>()
+                .ok()
+                .filter(|id| *id >= UNKNOWN_REPORTER_ID)
+                .ok_or_else(|| format!("ユーザーID「{}」が正しくありません", user_id))?,
+        };
+
+        let voted_at = match (columns.voted_at.map(field), columns.date.zip(columns.time)) {
+            (Some(voted_at), _) if !voted_at.is_empty() => DateTime::parse_from_rfc3339(voted_at)
+                .map_err(|_| format!("日時「{}」を読み取れません", voted_at))?
+                .with_timezone(&Utc),
+            (_, Some((date, time))) => Self::parse_local_datetime(field(date), field(time))?,
+            (_, None) => return Err("日時が空です".to_string()),
+        };
+
+        Ok(ImportRow {
+            user_id,
+            action: action.to_string(),
+            voted_at,
+        })
+    }
+
+    /// 投票期間のタイムゾーン（既定は日本時間）で書かれた日付と時刻をUTCに変換する
+    fn parse_local_datetime(date: &str, time: &str) -> Result<DateTime<Utc>, String> {
+        let date = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .or_else(|_| NaiveDate::parse_from_str(date, "%Y/%m/%d"))
+            .map_err(|_| format!("日付「{}」を読み取れません", date))?;
+
+        let time = NaiveTime::parse_from_str(time, "%H:%M")
+            .or_else(|_| NaiveTime::parse_from_str(time, "%H:%M:%S"))
+            .map_err(|_| format!("時刻「{}」を読み取れません", time))?;
+
+        Ok(VoteService::period_timezone()
+            .from_local_datetime(&date.and_time(time))
+            .single()
+            .ok_or_else(|| format!("日時「{} {}」を変換できません", date, time))?
+            .with_timezone(&Utc))
+    }
+
+    /// 選択肢のアクション名か表示名（日本語・英語）からアクション名を求める
+    /// `/export`で書き出した履歴に含まれる取り消しの記録も受け付ける
+    fn parse_action(value: &str) -> Option<&'static str> {
+        if value.eq_ignore_ascii_case(RETRACT_ACTION) {
+            return Some(RETRACT_ACTION);
+        }
+        VOTE_OPTIONS
+            .iter()
+            .find(|option| {
+                value.eq_ignore_ascii_case(option.action)
+                    || value == option.label
+                    || value.eq_ignore_ascii_case(option.label_en)
+            })
+            .map(|option| option.action)
+    }
+
+    /// CSVを行ごとのフィールドに分割する
+    /// ダブルクォートで囲まれたフィールド内のカンマ・改行・`""`に対応し、
+    /// 各レコードが始まる行番号と一緒に返す
+    fn parse_csv(content: &str) -> Vec<(usize, Vec<String>)> {
+        let mut records = Vec::new();
+        let mut record = Vec::new();
+        let mut field = String::new();
+        let mut in_quotes = false;
+        let mut line = 1;
+        let mut record_line = 1;
+
+        let mut chars = content.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '"' if in_quotes => {
+                    if chars.peek() == Some(&'"') {
+                        field.push('"');
+                        chars.next();
+                    } else {
+                        in_quotes = false;
+                    }
+                }
+                '"' if field.is_empty() => in_quotes = true,
+                ',' if !in_quotes => record.push(std::mem::take(&mut field)),
+                '\r' if !in_quotes => {}
+                '\n' => {
+                    line += 1;
+                    if in_quotes {
+                        field.push(c);
+                    } else {
+                        record.push(std::mem::take(&mut field));
+                        records.push((record_line, std::mem::take(&mut record)));
+                        record_line = line;
+                    }
+                }
+                _ => field.push(c),
+            }
+        }
+        if !field.is_empty() || !record.is_empty() {
+            record.push(field);
+            records.push((record_line, record));
+        }
+
+        records
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::export_service::{ExportService, VoteRecord};
+    use crate::services::test_support::{TEST_GUILD_ID, test_period};
+
+    fn voted_at(hour: u32, minute: u32) -> DateTime<Utc> {
+        test_period()
+            .and_time(NaiveTime::from_hms_opt(hour, minute, 0).unwrap())
+            .and_utc()
+    }
+
+    #[test]
+    fn csv_fields_can_be_quoted() {
+        let records =
+            ImportService::parse_csv("a,\"b, c\",\"say \"\"hi\"\"\"\r\n\"multi\nline\",x\nlast");
+
+        assert_eq!(
+            records,
+            vec![
+                (1, vec!["a".into(), "b, c".into(), "say \"hi\"".into()]),
+                (2, vec!["multi\nline".into(), "x".into()]),
+                (4, vec!["last".into()]),
+            ]
+        );
+    }
+
+    #[test]
+    fn header_without_date_or_voted_at_is_rejected() {
+        let error = ImportService::parse_history_csv("date,action\n2024-05-01,found\n")
+            .expect_err("time列がないのに読み取れた");
+        assert!(error.contains("time"), "{error}");
+
+        assert!(ImportService::parse_history_csv("").is_err());
+    }
+
+    #[test]
+    fn duplicate_rows_are_counted_not_dropped() {
+        let parsed = ImportService::parse_history_csv(
+            "\u{feff}date,time,action,user_id\n\
+             2024-05-01,12:30,営業してる,\n\
+             2024/05/01,12:30,found,\n\
+             2024-05-01,12:30,found,42\n\
+             \n\
+             2024-05-01,12:30,closed,42\n",
+        )
+        .unwrap();
+
+        assert_eq!(parsed.rows.len(), 2);
+        assert_eq!(parsed.rows[0].0.user_id, UNKNOWN_REPORTER_ID);
+        assert_eq!(parsed.rows[0].0.action, "found");
+        assert_eq!(parsed.rows[0].1, 2);
+        assert_eq!(parsed.rows[1].0.user_id, 42);
+        assert_eq!(parsed.rows[1].1, 1);
+        assert_eq!(parsed.errors.len(), 1);
+        assert_eq!(parsed.errors[0].line, 6);
+    }
+
+    #[test]
+    fn exported_history_can_be_imported() {
+        let records: Vec<VoteRecord> = [
+            (UNKNOWN_REPORTER_ID, "found", voted_at(3, 0)),
+            (UNKNOWN_REPORTER_ID, "found", voted_at(3, 0)),
+            (42, "sold_out", voted_at(4, 15)),
+            (42, RETRACT_ACTION, voted_at(4, 20)),
+        ]
+        .into_iter()
+        .map(|(user_id, action, voted_at)| VoteRecord {
+            period_date: test_period(),
+            user_id,
+            guild_id: Some(TEST_GUILD_ID),
+            action: action.to_string(),
+            voted_at: voted_at.fixed_offset(),
+        })
+        .collect();
+        let csv = String::from_utf8(ExportService::votes_to_csv(&records)).unwrap();
+
+        let parsed = ImportService::parse_history_csv(&csv).unwrap();
+
+        assert!(parsed.errors.is_empty(), "{:?}", parsed.errors);
+        let rows: Vec<(i64, &str, DateTime<Utc>, u64)> = parsed
+            .rows
+            .iter()
+            .map(|(row, count)| (row.user_id, row.action.as_str(), row.voted_at, *count))
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                (UNKNOWN_REPORTER_ID, "found", voted_at(3, 0), 2),
+                (42, "sold_out", voted_at(4, 15), 1),
+                (42, RETRACT_ACTION, voted_at(4, 20), 1),
+            ]
+        );
+    }
+}
@@ -1,10 +1,40 @@
+pub mod audit_log;
 pub mod board_service;
 pub mod board_ui_service;
+pub mod chart_cache;
 pub mod chart_service;
+pub mod chart_theme;
+pub mod export_service;
+pub mod guild_settings_service;
+pub mod import_service;
+pub mod moderation_service;
+pub mod report_service;
+pub mod reputation_service;
+pub mod stats_service;
+#[cfg(test)]
+pub(crate) mod test_support;
+pub mod vote_cooldown;
 pub mod vote_service;
+pub mod voter_eligibility;
 
 // Re-export services for easier access
+pub use audit_log::{AuditEvent, AuditLog};
 pub use board_service::BoardService;
 pub use board_ui_service::BoardUIService;
-pub use chart_service::ChartService;
-pub use vote_service::VoteService;
+pub use chart_cache::ChartCache;
+pub use chart_service::{ChartOutput, ChartService, ChartStyle};
+pub use chart_theme::{ChartLocale, ChartTheme};
+pub use export_service::{ExportFormat, ExportKind, ExportRange, ExportRequest, ExportService};
+pub use guild_settings_service::{ChartThemeUpdate, GuildSettingsService};
+pub use import_service::ImportService;
+pub use moderation_service::ModerationService;
+pub use report_service::{BOARD_REPORT_LIMIT, MAX_REPORT_CHARS, ReportPhoto, ReportService};
+pub use reputation_service::{
+    MAX_REPUTATION, Reputation, ReputationCache, ReputationService, WeightedTally,
+};
+pub use stats_service::{
+    LeaderboardSort, MIN_ACCURACY_PERIODS, StatsService, StatsWindow, UserStats,
+};
+pub use vote_cooldown::{VoteCooldown, VoteCooldownKey};
+pub use vote_service::{RETRACT_ACTION, RetractOutcome, VOTE_OPTIONS, VoteOption, VoteService};
+pub use voter_eligibility::{Ineligibility, VoteEligibility, Voter};
@@ -0,0 +1,151 @@
+use crate::entities::{voter_ban, voter_ban::Model as VoterBanModel};
+use crate::services::audit_log::AuditEvent;
+use crate::services::vote_service::VoteService;
+use chrono::{DateTime, Utc};
+use sea_orm::*;
+use std::collections::HashSet;
+use tracing::{info, instrument};
+
+pub struct ModerationService;
+
+impl ModerationService {
+    /// モデレーターの操作でユーザーの現在の投票を削除する（取り消しとして投票履歴に記録する）
+    /// 現在の期間にこのサーバーで投票していなかった場合は`None`、削除した場合は削除した投票のアクション名を返す
+    /// 投票はサーバーをまたいで1つなので、他のサーバーの掲示板から投票したユーザーの投票は削除しない
+    #[instrument(skip(data))]
+    pub async fn remove_vote(
+        data: &crate::Data,
+        guild_id: i64,
+        target_user_id: i64,
+        moderator_id: i64,
+    ) -> Result<Option<String>, DbErr> {
+        let voter_ids = VoteService::get_guild_voter_ids(&data.database, guild_id).await?;
+        if !voter_ids.contains(&target_user_id) {
+            return Ok(None);
+        }
+
+        let Some(vote) =
+            VoteService::retract_vote(&data.database, target_user_id, Some(guild_id)).await?
+        else {
+            return Ok(None);
+        };
+
+        data.chart_cache.invalidate();
+        info!(old_action = %vote.action, "モデレーターが投票を削除しました");
+        data.audit_log.record(
+            guild_id,
+            AuditEvent::VoteRemoved {
+                user_id: moderator_id,
+                target_user_id,
+                old_action: vote.action.clone(),
+            },
+        );
+        Ok(Some(vote.action))
+    }
+
+    /// サーバーでの投票を禁止する（`expires_at = None`なら解除するまで禁止する）
+    /// 既に禁止されている場合は期限と理由を置き換える
+    #[instrument(skip(db))]
+    pub async fn ban_voter(
+        db: &DatabaseConnection,
+        guild_id: i64,
+        user_id: i64,
+        banned_by: i64,
+        reason: Option<String>,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Result<VoterBanModel, DbErr> {
+        let now = Utc::now();
+        let existing = Self::find_ban(db, guild_id, user_id).await?;
+
+        match existing {
+            Some(existing) => {
+                let mut ban: voter_ban::ActiveModel = existing.into();
+                ban.banned_by = Set(banned_by);
+                ban.reason = Set(reason);
+                ban.expires_at = Set(expires_at.map(VoteService::to_db_timestamp));
+                ban.created_at = Set(VoteService::to_db_timestamp(now));
+                ban.update(db).await
+            }
+            None => {
+                let ban = voter_ban::ActiveModel {
+                    guild_id: Set(guild_id),
+                    user_id: Set(user_id),
+                    banned_by: Set(banned_by),
+                    reason: Set(reason),
+                    expires_at: Set(expires_at.map(VoteService::to_db_timestamp)),
+                    created_at: Set(VoteService::to_db_timestamp(now)),
+                    ..Default::default()
+                };
+                ban.insert(db).await
+            }
+        }
+    }
+
+    /// 投票の禁止を解除する（禁止されていなかった場合は`false`）
+    #[instrument(skip(db))]
+    pub async fn unban_voter(
+        db: &DatabaseConnection,
+        guild_id: i64,
+        user_id: i64,
+    ) -> Result<bool, DbErr> {
+        let result = voter_ban::Entity::delete_many()
+            .filter(voter_ban::Column::GuildId.eq(guild_id))
+            .filter(voter_ban::Column::UserId.eq(user_id))
+            .exec(db)
+            .await?;
+        Ok(result.rows_affected > 0)
+    }
+
+    /// 現在有効な投票の禁止を取得する（期限が過ぎていれば`None`）
+    pub async fn get_active_ban(
+        db: &DatabaseConnection,
+        guild_id: i64,
+        user_id: i64,
+        now: DateTime<Utc>,
+    ) -> Result<Option<VoterBanModel>, DbErr> {
+        Ok(Self::find_ban(db, guild_id, user_id)
+            .await?
+            .filter(|ban| ban.expires_at.is_none_or(|expires_at| expires_at > now)))
+    }
+
+    /// サーバーで現在投票を禁止されているユーザーのID
+    pub async fn get_banned_user_ids(
+        db: &DatabaseConnection,
+        guild_id: i64,
+        now: DateTime<Utc>,
+    ) -> Result<HashSet<i64>, DbErr> {
+        let bans = voter_ban::Entity::find()
+            .filter(voter_ban::Column::GuildId.eq(guild_id))
+            .filter(
+                Condition::any()
+                    .add(voter_ban::Column::ExpiresAt.is_null())
+                    .add(voter_ban::Column::ExpiresAt.gt(VoteService::to_db_timestamp(now))),
+            )
+            .all(db)
+            .await?;
+        Ok(bans.into_iter().map(|ban| ban.user_id).collect())
+    }
+
+    /// 投票を禁止されたユーザーへの返答
+    pub fn ban_message(ban: &VoterBanModel) -> String {
+        match ban.expires_at {
+            Some(expires_at) => format!(
+                "🚫 このサーバーでは<t:{}:f>まで投票できません。",
+                expires_at.timestamp()
+            ),
+            None => "🚫 このサーバーでは投票できません。".to_string(),
+        }
+    }
+
+    async fn find_ban(
+        db: &DatabaseConnection,
+        guild_id: i64,
+        user_id: i64,
+    ) -> Result<Option<VoterBanModel>, DbErr> {
+        voter_ban::Entity::find()
+            .filter(voter_ban::Column::GuildId.eq(guild_id))
+            .filter(voter_ban::Column::UserId.eq(user_id))
+            .one(db)
+            .await
+    }
+}
@@ -0,0 +1,301 @@
+use crate::Error;
+use crate::entities::{vote_report, vote_report::Model as ReportModel};
+use crate::services::vote_service::VoteService;
+use chrono::{DateTime, NaiveDate, TimeDelta, Utc};
+use poise::serenity_prelude::{ChannelId, Http, MessageId};
+use sea_orm::*;
+use tracing::instrument;
+
+/// 報告の最大文字数
+pub const MAX_REPORT_CHARS: usize = 200;
+/// 掲示板に表示する報告の件数
+pub const BOARD_REPORT_LIMIT: u64 = 3;
+/// 写真のURLの期限がこれより近ければ、掲示板を更新する前にURLを取得し直す
+const PHOTO_URL_REFRESH_MARGIN: TimeDelta = TimeDelta::hours(1);
+
+/// 報告の写真を投稿し直したメッセージ
+/// DiscordのCDNのURLは署名付きで1日ほどで期限が切れるため、URLと一緒にメッセージを保存しておき、
+/// 期限が近づいたらメッセージを取得し直して新しいURLにする
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReportPhoto {
+    pub url: String,
+    pub channel_id: i64,
+    pub message_id: i64,
+}
+
+pub struct ReportService;
+
+impl ReportService {
+    /// 報告の内容を1行にまとめる（改行や連続する空白は1つの空白にし、長すぎる場合は切り詰める）
+    /// 空白しかない場合は`None`
+    pub fn normalize_note(note: &str) -> Option<String> {
+        let note = note
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .chars()
+            .take(MAX_REPORT_CHARS)
+            .collect::<String>();
+        (!note.is_empty()).then_some(note)
+    }
+
+    /// 報告を1行に整形する（時刻は閲覧する人のタイムゾーンで表示される）
+    /// 写真付きの報告には写真を投稿し直したメッセージへのリンクを付ける
+    pub fn format_report(report: &ReportModel) -> String {
+        let action = report
+            .action
+            .as_deref()
+            .map(|action| format!("（{}）", VoteService::action_label(action)))
+            .unwrap_or_default();
+        let photo = Self::photo_link(report).map(|url| format!("[📷 写真]({})", url));
+        let body = match (report.note.is_empty(), photo) {
+            (false, Some(photo)) => format!("{} {}", report.note, photo),
+            (true, Some(photo)) => photo,
+            (_, None) => report.note.clone(),
+        };
+        format!(
+            "<t:{}:t> <@{}>{}: {}",
+            report.created_at.timestamp(),
+            report.user_id,
+            action,
+            body
+        )
+    }
+
+    /// 写真へのリンク（メッセージが分かればメッセージへのリンク、分からない古い報告は保存したURL）
+    fn photo_link(report: &ReportModel) -> Option<String> {
+        match (
+            report.guild_id,
+            report.photo_channel_id,
+            report.photo_message_id,
+        ) {
+            (Some(guild_id), Some(channel_id), Some(message_id)) => Some(format!(
+                "https://discord.com/channels/{}/{}/{}",
+                guild_id, channel_id, message_id
+            )),
+            _ => report.photo_url.clone(),
+        }
+    }
+
+    /// DiscordのCDNのURLの期限（`ex`パラメーターの16進数のUNIX時刻）
+    /// 期限の付いていないURLは`None`
+    pub fn photo_url_expires_at(url: &str) -> Option<DateTime<Utc>> {
+        let (_, query) = url.split_once('?')?;
+        let expires_at = query
+            .split('&')
+            .find_map(|param| param.strip_prefix("ex="))?;
+        DateTime::from_timestamp(i64::from_str_radix(expires_at, 16).ok()?, 0)
+    }
+
+    /// 写真のURLの期限が近ければ、投稿し直したメッセージを取得し直して新しいURLを保存する
+    /// メッセージの分からない古い報告や、期限の近くないURLはそのまま返す
+    #[instrument(skip(http, db, report), fields(report_id = report.id))]
+    pub async fn refresh_photo_url(
+        http: &Http,
+        db: &DatabaseConnection,
+        report: ReportModel,
+    ) -> Result<ReportModel, Error> {
+        let (Some(url), Some(channel_id), Some(message_id)) = (
+            report.photo_url.as_deref(),
+            report.photo_channel_id,
+            report.photo_message_id,
+        ) else {
+            return Ok(report);
+        };
+        let expiring = Self::photo_url_expires_at(url)
+            .is_some_and(|expires_at| expires_at - Utc::now() < PHOTO_URL_REFRESH_MARGIN);
+        if !expiring {
+            return Ok(report);
+        }
+
+        let message = ChannelId::new(channel_id as u64)
+            .message(http, MessageId::new(message_id as u64))
+            .await?;
+        let Some(attachment) = message.attachments.first() else {
+            return Ok(report);
+        };
+
+        let mut report: vote_report::ActiveModel = report.into();
+        report.photo_url = Set(Some(attachment.url.clone()));
+        Ok(report.update(db).await?)
+    }
+
+    /// 非表示にした報告の写真を投稿し直したメッセージから取り下げる
+    /// 表示に戻す時に投稿し直せるよう、写真はメッセージを削除する前にダウンロードして保存しておく
+    /// メッセージの分からない古い報告はそのまま返す
+    #[instrument(skip(http, db, report), fields(report_id = report.id))]
+    pub async fn withdraw_photo(
+        http: &Http,
+        db: &DatabaseConnection,
+        report: ReportModel,
+    ) -> Result<ReportModel, Error> {
+        let (Some(channel_id), Some(message_id)) =
+            (report.photo_channel_id, report.photo_message_id)
+        else {
+            return Ok(report);
+        };
+
+        let channel_id = ChannelId::new(channel_id as u64);
+        let message_id = MessageId::new(message_id as u64);
+        let message = channel_id.message(http, message_id).await?;
+        let photo = match message.attachments.first() {
+            Some(attachment) => Some((attachment.download().await?, attachment.filename.clone())),
+            None => None,
+        };
+        channel_id.delete_message(http, message_id).await?;
+
+        let (hidden_photo, hidden_photo_filename) = photo.unzip();
+        let mut report: vote_report::ActiveModel = report.into();
+        report.photo_url = Set(None);
+        report.photo_message_id = Set(None);
+        report.hidden_photo = Set(hidden_photo);
+        report.hidden_photo_filename = Set(hidden_photo_filename);
+        Ok(report.update(db).await?)
+    }
+
+    /// 表示に戻した報告の写真を投稿し直したメッセージを保存し、取り下げた時の写真を消す
+    #[instrument(skip(db, report), fields(report_id = report.id))]
+    pub async fn restore_photo(
+        db: &DatabaseConnection,
+        report: ReportModel,
+        photo: ReportPhoto,
+    ) -> Result<ReportModel, DbErr> {
+        let mut report: vote_report::ActiveModel = report.into();
+        report.photo_url = Set(Some(photo.url));
+        report.photo_channel_id = Set(Some(photo.channel_id));
+        report.photo_message_id = Set(Some(photo.message_id));
+        report.hidden_photo = Set(None);
+        report.hidden_photo_filename = Set(None);
+        report.update(db).await
+    }
+
+    /// 現在の投票期間の報告を保存する（写真のみの報告は`note`を空にする）
+    /// 報告した時点のユーザーの投票も一緒に記録する（投票していなければ`None`）
+    #[instrument(skip(db, note))]
+    pub async fn create_report(
+        db: &DatabaseConnection,
+        user_id: i64,
+        guild_id: Option<i64>,
+        note: String,
+        photo: Option<ReportPhoto>,
+    ) -> Result<ReportModel, DbErr> {
+        let now = Utc::now();
+        let action = VoteService::get_vote(db, user_id)
+            .await?
+            .map(|vote| vote.action);
+
+        let report = vote_report::ActiveModel {
+            user_id: Set(user_id),
+            guild_id: Set(guild_id),
+            action: Set(action),
+            note: Set(note),
+            period_date: Set(VoteService::get_jst_afternoon_period(now)),
+            hidden: Set(false),
+            hidden_by: Set(None),
+            created_at: Set(VoteService::to_db_timestamp(now)),
+            photo_url: Set(photo.as_ref().map(|photo| photo.url.clone())),
+            photo_channel_id: Set(photo.as_ref().map(|photo| photo.channel_id)),
+            photo_message_id: Set(photo.as_ref().map(|photo| photo.message_id)),
+            hidden_photo: Set(None),
+            hidden_photo_filename: Set(None),
+            ..Default::default()
+        };
+
+        report.insert(db).await
+    }
+
+    /// サーバーで指定した投票期間に行われた、非表示にされていない報告を新しい順に取得する
+    pub async fn get_latest_visible_reports(
+        db: &DatabaseConnection,
+        guild_id: i64,
+        period: NaiveDate,
+        limit: u64,
+    ) -> Result<Vec<ReportModel>, DbErr> {
+        vote_report::Entity::find()
+            .filter(vote_report::Column::GuildId.eq(guild_id))
+            .filter(vote_report::Column::PeriodDate.eq(period))
+            .filter(vote_report::Column::Hidden.eq(false))
+            .order_by_desc(vote_report::Column::CreatedAt)
+            .order_by_desc(vote_report::Column::Id)
+            .limit(limit)
+            .all(db)
+            .await
+    }
+
+    /// サーバーで指定した投票期間に行われた、非表示にされていない写真付きの報告のうち、最も新しいものを取得する
+    pub async fn get_latest_visible_photo(
+        db: &DatabaseConnection,
+        guild_id: i64,
+        period: NaiveDate,
+    ) -> Result<Option<ReportModel>, DbErr> {
+        vote_report::Entity::find()
+            .filter(vote_report::Column::GuildId.eq(guild_id))
+            .filter(vote_report::Column::PeriodDate.eq(period))
+            .filter(vote_report::Column::Hidden.eq(false))
+            .filter(vote_report::Column::PhotoUrl.is_not_null())
+            .order_by_desc(vote_report::Column::CreatedAt)
+            .order_by_desc(vote_report::Column::Id)
+            .one(db)
+            .await
+    }
+
+    /// サーバーで指定した投票期間に行われた報告を、非表示にされたものも含めて新しい順に取得する
+    pub async fn get_period_reports(
+        db: &DatabaseConnection,
+        guild_id: i64,
+        period: NaiveDate,
+        limit: u64,
+    ) -> Result<Vec<ReportModel>, DbErr> {
+        vote_report::Entity::find()
+            .filter(vote_report::Column::GuildId.eq(guild_id))
+            .filter(vote_report::Column::PeriodDate.eq(period))
+            .order_by_desc(vote_report::Column::CreatedAt)
+            .order_by_desc(vote_report::Column::Id)
+            .limit(limit)
+            .all(db)
+            .await
+    }
+
+    /// サーバーの報告の表示・非表示を切り替える（報告が存在しないか、別のサーバーの報告なら`None`）
+    /// 非表示にする場合は操作したモデレーターを記録する
+    #[instrument(skip(db))]
+    pub async fn set_hidden(
+        db: &DatabaseConnection,
+        guild_id: i64,
+        report_id: i32,
+        hidden: bool,
+        moderator_id: i64,
+    ) -> Result<Option<ReportModel>, DbErr> {
+        let report = vote_report::Entity::find_by_id(report_id)
+            .filter(vote_report::Column::GuildId.eq(guild_id))
+            .one(db)
+            .await?;
+        let Some(report) = report else {
+            return Ok(None);
+        };
+
+        let mut report: vote_report::ActiveModel = report.into();
+        report.hidden = Set(hidden);
+        report.hidden_by = Set(hidden.then_some(moderator_id));
+        report.update(db).await.map(Some)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn photo_url_expiry_is_read_from_ex_parameter() {
+        let url =
+            "https://cdn.discordapp.com/attachments/1/2/kebab.png?ex=66b3c0f0&is=66b26f70&hm=abc";
+        assert_eq!(
+            ReportService::photo_url_expires_at(url),
+            DateTime::from_timestamp(0x66b3c0f0, 0)
+        );
+        assert_eq!(
+            ReportService::photo_url_expires_at("https://example.com/kebab.png"),
+            None
+        );
+    }
+}
@@ -0,0 +1,254 @@
+use crate::entities::{user_reputation, user_reputation::Model as UserReputationModel};
+use crate::services::guild_settings_service::GuildSettingsService;
+use crate::services::stats_service::{StatsService, UserStats};
+use crate::services::vote_service::{VOTE_OPTIONS, VoteService};
+use chrono::{NaiveDate, Utc};
+use sea_orm::*;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tracing::instrument;
+
+/// 信頼度の最大値
+pub const MAX_REPUTATION: u32 = 100;
+/// 判定できる期間がないユーザーの信頼度（票の重みが1になる値）
+pub const NEUTRAL_REPUTATION: f64 = 50.0;
+
+/// ユーザーの信頼度（0〜100、票の重みは0〜2倍）
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Reputation {
+    /// 過去の期間の多数派との一致から求めた信頼度
+    pub computed: f64,
+    /// 計算に使った、多数派が決まった期間の数
+    pub judged_periods: u64,
+    /// 管理者が設定した信頼度（設定されていれば計算値より優先する）
+    pub override_score: Option<u32>,
+}
+
+impl Default for Reputation {
+    fn default() -> Self {
+        Self {
+            computed: NEUTRAL_REPUTATION,
+            judged_periods: 0,
+            override_score: None,
+        }
+    }
+}
+
+impl Reputation {
+    /// 過去の統計から信頼度を求める
+    /// 判定した期間が少ないうちは一致・不一致の影響が大きくなりすぎないよう、1勝1敗から始めて数える
+    pub fn from_stats(stats: &UserStats) -> Self {
+        Self {
+            computed: (stats.agreed_periods + 1) as f64 / (stats.judged_periods + 2) as f64
+                * MAX_REPUTATION as f64,
+            judged_periods: stats.judged_periods,
+            override_score: None,
+        }
+    }
+
+    /// 実際に使う信頼度
+    pub fn score(&self) -> f64 {
+        self.override_score
+            .map_or(self.computed, |score| score as f64)
+    }
+
+    /// 票の重み（信頼度50で1票）
+    pub fn weight(&self) -> f64 {
+        self.score() / NEUTRAL_REPUTATION
+    }
+}
+
+/// 信頼度で重み付けした選択肢ごとの票数（`VOTE_OPTIONS`の順）
+#[derive(Debug, Clone, PartialEq)]
+pub struct WeightedTally {
+    pub counts: Vec<f64>,
+}
+
+impl WeightedTally {
+    /// 選択肢ごとに1行ずつ表示する
+    pub fn describe(&self) -> String {
+        VOTE_OPTIONS
+            .iter()
+            .zip(&self.counts)
+            .map(|(option, count)| format!("{} {}: {:.1}票", option.emoji, option.label, count))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// 重み付けした票数の合計
+    pub fn total(&self) -> f64 {
+        self.counts.iter().sum()
+    }
+}
+
+/// サーバーのユーザーごとの信頼度
+pub type GuildReputations = Arc<HashMap<i64, Reputation>>;
+
+/// サーバーごとの信頼度を保持するキャッシュ
+/// 信頼度は確定した過去の期間だけから求めるため、期間が変わるか管理者が設定を変えるまで同じ値になる
+#[derive(Default)]
+pub struct ReputationCache {
+    entries: Mutex<HashMap<i64, (NaiveDate, GuildReputations)>>,
+}
+
+impl ReputationCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 指定した期間に求めた信頼度を取得
+    pub fn get(&self, guild_id: i64, period: NaiveDate) -> Option<GuildReputations> {
+        self.lock()
+            .get(&guild_id)
+            .filter(|(cached_period, _)| *cached_period == period)
+            .map(|(_, reputations)| Arc::clone(reputations))
+    }
+
+    /// 求めた信頼度をキャッシュに保存
+    pub fn insert(
+        &self,
+        guild_id: i64,
+        period: NaiveDate,
+        reputations: HashMap<i64, Reputation>,
+    ) -> GuildReputations {
+        let reputations = Arc::new(reputations);
+        self.lock()
+            .insert(guild_id, (period, Arc::clone(&reputations)));
+        reputations
+    }
+
+    /// 管理者が信頼度を設定した時にサーバーの信頼度を破棄する
+    pub fn invalidate_guild(&self, guild_id: i64) {
+        self.lock().remove(&guild_id);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<i64, (NaiveDate, GuildReputations)>> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+pub struct ReputationService;
+
+impl ReputationService {
+    /// サーバーのユーザーごとの信頼度を取得する（記録のないユーザーは含まない）
+    pub async fn get_guild_reputations(
+        db: &DatabaseConnection,
+        cache: &ReputationCache,
+        guild_id: i64,
+    ) -> Result<GuildReputations, DbErr> {
+        let period = VoteService::get_current_jst_afternoon_period();
+        if let Some(reputations) = cache.get(guild_id, period) {
+            return Ok(reputations);
+        }
+
+        let mut reputations: HashMap<i64, Reputation> =
+            StatsService::get_settled_guild_stats(db, guild_id)
+                .await?
+                .iter()
+                .map(|stats| (stats.user_id, Reputation::from_stats(stats)))
+                .collect();
+        for entry in Self::get_overrides(db, guild_id).await? {
+            reputations.entry(entry.user_id).or_default().override_score =
+                Some(entry.override_score.clamp(0, MAX_REPUTATION as i32) as u32);
+        }
+
+        Ok(cache.insert(guild_id, period, reputations))
+    }
+
+    /// ユーザーの信頼度を取得する（記録がなければ既定の信頼度）
+    pub async fn get_user_reputation(
+        db: &DatabaseConnection,
+        cache: &ReputationCache,
+        guild_id: i64,
+        user_id: i64,
+    ) -> Result<Reputation, DbErr> {
+        let reputations = Self::get_guild_reputations(db, cache, guild_id).await?;
+        Ok(reputations.get(&user_id).copied().unwrap_or_default())
+    }
+
+    /// サーバーで管理者が設定した信頼度の一覧
+    pub async fn get_overrides(
+        db: &DatabaseConnection,
+        guild_id: i64,
+    ) -> Result<Vec<UserReputationModel>, DbErr> {
+        user_reputation::Entity::find()
+            .filter(user_reputation::Column::GuildId.eq(guild_id))
+            .order_by_asc(user_reputation::Column::UserId)
+            .all(db)
+            .await
+    }
+
+    /// ユーザーの信頼度を設定する（`None`なら設定を外して計算値に戻す）
+    #[instrument(skip(db))]
+    pub async fn set_override(
+        db: &DatabaseConnection,
+        guild_id: i64,
+        user_id: i64,
+        score: Option<u32>,
+        set_by: i64,
+    ) -> Result<(), DbErr> {
+        let existing = user_reputation::Entity::find()
+            .filter(user_reputation::Column::GuildId.eq(guild_id))
+            .filter(user_reputation::Column::UserId.eq(user_id))
+            .one(db)
+            .await?;
+
+        match (existing, score) {
+            (Some(existing), Some(score)) => {
+                let mut entry: user_reputation::ActiveModel = existing.into();
+                entry.override_score = Set(score as i32);
+                entry.set_by = Set(set_by);
+                entry.updated_at = Set(Utc::now().into());
+                entry.update(db).await?;
+            }
+            (None, Some(score)) => {
+                let entry = user_reputation::ActiveModel {
+                    guild_id: Set(guild_id),
+                    user_id: Set(user_id),
+                    override_score: Set(score as i32),
+                    set_by: Set(set_by),
+                    updated_at: Set(Utc::now().into()),
+                    ..Default::default()
+                };
+                entry.insert(db).await?;
+            }
+            (Some(existing), None) => {
+                user_reputation::Entity::delete_by_id(existing.id)
+                    .exec(db)
+                    .await?;
+            }
+            (None, None) => {}
+        }
+        Ok(())
+    }
+
+    /// サーバーで重み付けが有効な場合に、このサーバーで投票したユーザーの現在の投票を信頼度で重み付けして集計する
+    /// 信頼度はサーバーごとに求めるため、他のサーバーから投票したユーザーは数えない
+    /// 無効な場合は`None`
+    pub async fn get_weighted_tally(
+        db: &DatabaseConnection,
+        cache: &ReputationCache,
+        guild_id: i64,
+    ) -> Result<Option<WeightedTally>, DbErr> {
+        if !GuildSettingsService::get_reputation_weighting(db, guild_id).await? {
+            return Ok(None);
+        }
+
+        let reputations = Self::get_guild_reputations(db, cache, guild_id).await?;
+        let mut counts = vec![0.0; VOTE_OPTIONS.len()];
+        for vote in VoteService::get_guild_votes(db, guild_id).await? {
+            let Some(index) = VOTE_OPTIONS
+                .iter()
+                .position(|option| option.action == vote.action)
+            else {
+                continue;
+            };
+            counts[index] += reputations
+                .get(&vote.user_id)
+                .copied()
+                .unwrap_or_default()
+                .weight();
+        }
+        Ok(Some(WeightedTally { counts }))
+    }
+}
@@ -0,0 +1,295 @@
+use crate::entities::vote_history;
+use crate::services::import_service::UNKNOWN_REPORTER_ID;
+use crate::services::vote_service::{RETRACT_ACTION, VoteService};
+use chrono::{Datelike, Days, NaiveDate};
+use sea_orm::*;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// 的中率の順位に載るために必要な、合意が決まった期間の数
+pub const MIN_ACCURACY_PERIODS: u64 = 3;
+
+/// 統計を集計する範囲
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatsWindow {
+    /// 今週（月曜日の期間から現在の期間まで）
+    Weekly,
+    /// 全期間
+    AllTime,
+}
+
+impl StatsWindow {
+    /// 範囲の最初の期間の日付（全期間なら`None`）
+    pub fn start(&self, current_period: NaiveDate) -> Option<NaiveDate> {
+        match self {
+            StatsWindow::Weekly => Some(
+                current_period - Days::new(current_period.weekday().num_days_from_monday() as u64),
+            ),
+            StatsWindow::AllTime => None,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            StatsWindow::Weekly => "今週",
+            StatsWindow::AllTime => "全期間",
+        }
+    }
+}
+
+/// ランキングの並び順
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LeaderboardSort {
+    /// 報告数
+    Reports,
+    /// 「営業してる」の一番乗りの回数
+    FirstFound,
+    /// 期間の最終的な多数派との一致率
+    Accuracy,
+    /// 最長の連続投票日数
+    Streak,
+}
+
+/// ユーザーごとの統計
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UserStats {
+    pub user_id: i64,
+    /// 投票した回数（変更を含み、取り消しは含まない）
+    pub reports: u64,
+    /// 期間で最初に「営業してる」に投票し、期間の終わりまで変えなかった回数
+    pub first_found: u64,
+    /// 最終的な投票があり、期間の多数派が決まった期間の数
+    pub judged_periods: u64,
+    /// そのうち最終的な投票が多数派と一致した期間の数
+    pub agreed_periods: u64,
+    /// 現在の期間（または前の期間）まで続いている連続投票日数
+    pub current_streak: u32,
+    /// 範囲内で最長の連続投票日数
+    pub best_streak: u32,
+}
+
+impl UserStats {
+    /// 多数派との一致率（判定できる期間がなければ`None`）
+    pub fn accuracy(&self) -> Option<f64> {
+        (self.judged_periods > 0).then(|| self.agreed_periods as f64 / self.judged_periods as f64)
+    }
+}
+
+pub struct StatsService;
+
+impl StatsService {
+    /// サーバーで行われた投票の履歴から、ユーザーごとの統計を集計する
+    pub async fn get_guild_stats(
+        db: &DatabaseConnection,
+        guild_id: i64,
+        window: StatsWindow,
+    ) -> Result<Vec<UserStats>, DbErr> {
+        let current_period = VoteService::get_current_jst_afternoon_period();
+        let mut query = vote_history::Entity::find()
+            .filter(vote_history::Column::GuildId.eq(guild_id))
+            .filter(vote_history::Column::PeriodDate.lte(current_period))
+            .order_by_asc(vote_history::Column::CreatedAt)
+            .order_by_asc(vote_history::Column::Id);
+        if let Some(start) = window.start(current_period) {
+            query = query.filter(vote_history::Column::PeriodDate.gte(start));
+        }
+        let history = query.all(db).await?;
+
+        Ok(Self::compute_stats(&history, current_period))
+    }
+
+    /// 現在の期間より前の、結果の確定した期間の履歴だけからユーザーごとの統計を集計する
+    pub async fn get_settled_guild_stats(
+        db: &DatabaseConnection,
+        guild_id: i64,
+    ) -> Result<Vec<UserStats>, DbErr> {
+        let current_period = VoteService::get_current_jst_afternoon_period();
+        let history = vote_history::Entity::find()
+            .filter(vote_history::Column::GuildId.eq(guild_id))
+            .filter(vote_history::Column::PeriodDate.lt(current_period))
+            .order_by_asc(vote_history::Column::CreatedAt)
+            .order_by_asc(vote_history::Column::Id)
+            .all(db)
+            .await?;
+
+        Ok(Self::compute_stats(&history, current_period))
+    }
+
+    /// 古い順に並んだ投票履歴からユーザーごとの統計を集計する（ユーザーID順）
+    /// 記録者が分からない取り込み済みの履歴は多数派の判定にだけ使い、統計には含めない
+    pub fn compute_stats(
+        history: &[vote_history::Model],
+        current_period: NaiveDate,
+    ) -> Vec<UserStats> {
+        let mut stats: BTreeMap<i64, UserStats> = BTreeMap::new();
+        let mut periods: BTreeMap<NaiveDate, Vec<&vote_history::Model>> = BTreeMap::new();
+        for entry in history {
+            periods.entry(entry.period_date).or_default().push(entry);
+            if entry.user_id != UNKNOWN_REPORTER_ID && entry.action != RETRACT_ACTION {
+                Self::entry(&mut stats, entry.user_id).reports += 1;
+            }
+        }
+
+        let mut voted_periods: HashMap<i64, BTreeSet<NaiveDate>> = HashMap::new();
+        for (period, entries) in &periods {
+            // 履歴は古い順なので、後の記録で上書きすれば期間内の最後の投票になる
+            let mut final_votes: HashMap<i64, &str> = HashMap::new();
+            // 記録者が分からない履歴は1行ずつ別の投票として数える
+            let mut anonymous_votes: Vec<&str> = Vec::new();
+            for entry in entries {
+                if entry.user_id == UNKNOWN_REPORTER_ID {
+                    if entry.action != RETRACT_ACTION {
+                        anonymous_votes.push(&entry.action);
+                    }
+                } else if entry.action == RETRACT_ACTION {
+                    final_votes.remove(&entry.user_id);
+                } else {
+                    final_votes.insert(entry.user_id, &entry.action);
+                }
+            }
+
+            // 最後まで「営業してる」のままだった投票のうち最初のもの
+            let first_found = entries
+                .iter()
+                .find(|entry| {
+                    entry.action == "found" && final_votes.get(&entry.user_id) == Some(&"found")
+                })
+                .map(|entry| entry.user_id);
+            if let Some(user_id) = first_found {
+                Self::entry(&mut stats, user_id).first_found += 1;
+            }
+
+            let consensus = Self::consensus(
+                final_votes
+                    .values()
+                    .copied()
+                    .chain(anonymous_votes.iter().copied()),
+            );
+            for (&user_id, &action) in &final_votes {
+                voted_periods.entry(user_id).or_default().insert(*period);
+                if let Some(consensus) = consensus {
+                    let user = Self::entry(&mut stats, user_id);
+                    user.judged_periods += 1;
+                    if action == consensus {
+                        user.agreed_periods += 1;
+                    }
+                }
+            }
+        }
+
+        for (user_id, periods) in voted_periods {
+            let (current_streak, best_streak) = Self::streaks(&periods, current_period);
+            let user = Self::entry(&mut stats, user_id);
+            user.current_streak = current_streak;
+            user.best_streak = best_streak;
+        }
+
+        stats.into_values().collect()
+    }
+
+    /// 指定した並び順で上位のユーザーを返す
+    /// 的中率の順位には判定できる期間が`MIN_ACCURACY_PERIODS`以上のユーザーだけを載せる
+    pub fn rank(mut stats: Vec<UserStats>, sort: LeaderboardSort, limit: usize) -> Vec<UserStats> {
+        match sort {
+            LeaderboardSort::Reports => {
+                stats.retain(|user| user.reports > 0);
+                stats.sort_by(|a, b| {
+                    b.reports
+                        .cmp(&a.reports)
+                        .then(b.first_found.cmp(&a.first_found))
+                });
+            }
+            LeaderboardSort::FirstFound => {
+                stats.retain(|user| user.first_found > 0);
+                stats.sort_by(|a, b| {
+                    b.first_found
+                        .cmp(&a.first_found)
+                        .then(b.reports.cmp(&a.reports))
+                });
+            }
+            LeaderboardSort::Accuracy => {
+                stats.retain(|user| user.judged_periods >= MIN_ACCURACY_PERIODS);
+                stats.sort_by(|a, b| {
+                    // a/b と c/d の比較を整数で行う
+                    (b.agreed_periods * a.judged_periods)
+                        .cmp(&(a.agreed_periods * b.judged_periods))
+                        .then(b.judged_periods.cmp(&a.judged_periods))
+                });
+            }
+            LeaderboardSort::Streak => {
+                stats.retain(|user| user.best_streak > 0);
+                stats.sort_by(|a, b| {
+                    b.best_streak
+                        .cmp(&a.best_streak)
+                        .then(b.current_streak.cmp(&a.current_streak))
+                });
+            }
+        }
+        stats.truncate(limit);
+        stats
+    }
+
+    /// 期間の最終的な投票で最も多い選択肢（同数で並んだ場合は決まらない）
+    fn consensus<'a>(actions: impl Iterator<Item = &'a str>) -> Option<&'a str> {
+        let mut counts: HashMap<&str, u64> = HashMap::new();
+        for action in actions {
+            *counts.entry(action).or_default() += 1;
+        }
+        let max = *counts.values().max()?;
+        let mut leaders = counts.into_iter().filter(|(_, count)| *count == max);
+        let (action, _) = leaders.next()?;
+        leaders.next().is_none().then_some(action)
+    }
+
+    /// 投票した期間の集合から、現在まで続いている連続日数と最長の連続日数を求める
+    /// 現在の期間にまだ投票していなくても、前の期間まで続いていれば継続中とみなす
+    fn streaks(periods: &BTreeSet<NaiveDate>, current_period: NaiveDate) -> (u32, u32) {
+        let mut best = 0;
+        let mut run = 0;
+        let mut previous: Option<NaiveDate> = None;
+        for &period in periods {
+            run = match previous {
+                Some(previous) if previous.succ_opt() == Some(period) => run + 1,
+                _ => 1,
+            };
+            best = best.max(run);
+            previous = Some(period);
+        }
+
+        let current = match previous {
+            Some(last) if last == current_period || last.succ_opt() == Some(current_period) => run,
+            _ => 0,
+        };
+        (current, best)
+    }
+
+    fn entry(stats: &mut BTreeMap<i64, UserStats>, user_id: i64) -> &mut UserStats {
+        stats.entry(user_id).or_insert_with(|| UserStats {
+            user_id,
+            ..Default::default()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::test_support::{history, test_period};
+
+    #[test]
+    fn anonymous_rows_in_same_period_each_count_toward_consensus() {
+        // 記録者不明の2行が「営業してる」なので、1人の「いない」より多い
+        let stats = StatsService::compute_stats(
+            &[
+                history(1, UNKNOWN_REPORTER_ID, "found", 0),
+                history(2, UNKNOWN_REPORTER_ID, "found", 5),
+                history(3, 42, "not_found", 10),
+            ],
+            test_period() + Days::new(1),
+        );
+
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].user_id, 42);
+        assert_eq!(stats[0].judged_periods, 1);
+        assert_eq!(stats[0].agreed_periods, 0);
+    }
+}
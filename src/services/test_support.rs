@@ -0,0 +1,46 @@
+//! サービスのテストで共通に使うデータ
+
+use crate::entities::vote_history;
+use chrono::{Days, NaiveDate, TimeZone, Utc};
+
+/// テストの投票履歴のサーバーID
+pub const TEST_GUILD_ID: i64 = 1;
+
+/// テストで使う投票期間
+pub fn test_period() -> NaiveDate {
+    NaiveDate::from_ymd_opt(2024, 5, 1).unwrap()
+}
+
+/// テストで使う投票期間の12時`minute`分（日本時間）の投票履歴
+pub fn history(id: i32, user_id: i64, action: &str, minute: u32) -> vote_history::Model {
+    history_on(test_period(), id, user_id, action, minute)
+}
+
+/// `days`日後の投票期間の12時`minute`分（日本時間）の投票履歴
+pub fn history_after(
+    days: u64,
+    id: i32,
+    user_id: i64,
+    action: &str,
+    minute: u32,
+) -> vote_history::Model {
+    history_on(test_period() + Days::new(days), id, user_id, action, minute)
+}
+
+fn history_on(
+    period: NaiveDate,
+    id: i32,
+    user_id: i64,
+    action: &str,
+    minute: u32,
+) -> vote_history::Model {
+    let voted_at = period.and_hms_opt(3, minute, 0).unwrap();
+    vote_history::Model {
+        id,
+        user_id,
+        guild_id: Some(TEST_GUILD_ID),
+        action: action.to_string(),
+        period_date: period,
+        created_at: Utc.from_utc_datetime(&voted_at).into(),
+    }
+}
@@ -0,0 +1,78 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// 連打対策の対象（サーバーごと・ユーザーごと、DMなどサーバー外は`guild_id = None`）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct VoteCooldownKey {
+    pub guild_id: Option<i64>,
+    pub user_id: i64,
+}
+
+impl VoteCooldownKey {
+    /// 投票の変更・取り消しの対象
+    /// 投票はサーバーをまたいでユーザーごとに1つなので、どのサーバーの掲示板から押しても同じ待ち時間にする
+    pub fn vote(user_id: i64) -> Self {
+        Self {
+            guild_id: None,
+            user_id,
+        }
+    }
+}
+
+/// 投票を受け付けた時刻を覚えておき、一定時間内の投票の変更を断る
+/// 投票のたびにグラフの描画と掲示板の編集が走るため、ボタンの連打でDiscord APIを使い切らないようにする
+pub struct VoteCooldown {
+    cooldown: Duration,
+    last_votes: Mutex<HashMap<VoteCooldownKey, Instant>>,
+}
+
+impl VoteCooldown {
+    /// `cooldown`が0なら制限しない
+    pub fn new(cooldown: Duration) -> Self {
+        Self {
+            cooldown,
+            last_votes: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 投票できる場合は受け付けた時刻を記録して`Ok`、待ち時間が残っている場合はその時間を返す
+    /// 確認と記録を同時に行うため、同時に押されたボタンも1つしか通らない
+    pub fn try_acquire(&self, key: VoteCooldownKey) -> Result<(), Duration> {
+        if self.cooldown.is_zero() {
+            return Ok(());
+        }
+
+        let now = Instant::now();
+        let mut last_votes = self.lock();
+        if let Some(remaining) = last_votes
+            .get(&key)
+            .and_then(|last| self.cooldown.checked_sub(now.duration_since(*last)))
+            .filter(|remaining| !remaining.is_zero())
+        {
+            return Err(remaining);
+        }
+
+        // 待ち時間が過ぎた記録は不要なので、記録のたびに捨てる
+        last_votes.retain(|_, last| now.duration_since(*last) < self.cooldown);
+        last_votes.insert(key, now);
+        Ok(())
+    }
+
+    /// 記録はせずに、待ち時間が残っていればその時間を返す
+    pub fn remaining(&self, key: VoteCooldownKey) -> Option<Duration> {
+        let last = *self.lock().get(&key)?;
+        self.cooldown
+            .checked_sub(last.elapsed())
+            .filter(|remaining| !remaining.is_zero())
+    }
+
+    /// 投票の保存に失敗した場合などに記録を取り消し、すぐにやり直せるようにする
+    pub fn release(&self, key: VoteCooldownKey) {
+        self.lock().remove(&key);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<VoteCooldownKey, Instant>> {
+        self.last_votes.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
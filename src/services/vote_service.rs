@@ -1,17 +1,92 @@
-use crate::entities::{vote, vote::Model as VoteModel};
-use chrono::{NaiveDate, Timelike, Utc};
-use chrono_tz::Asia::Tokyo;
+use crate::config::{Config, PeriodConfig};
+use crate::entities::{vote, vote::Model as VoteModel, vote_history};
+use crate::metrics::METRICS;
+use crate::services::audit_log::AuditEvent;
+use crate::services::chart_cache::VoteDataVersion;
+use crate::services::import_service::UNKNOWN_REPORTER_ID;
+use crate::services::moderation_service::ModerationService;
+use crate::services::vote_cooldown::VoteCooldownKey;
+use chrono::{DateTime, NaiveDate, Timelike, Utc};
+use chrono_tz::Tz;
+use sea_orm::prelude::DateTimeWithTimeZone;
 use sea_orm::*;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::OnceLock;
+use tracing::{info, instrument};
+
+/// 起動時に設定された投票期間の区切り方
+static PERIOD_CONFIG: OnceLock<PeriodConfig> = OnceLock::new();
+
+/// 投票の選択肢の定義
+pub struct VoteOption {
+    pub action: &'static str,
+    pub label: &'static str,
+    pub label_en: &'static str,
+    pub emoji: &'static str,
+}
+
+/// 掲示板に表示する投票の選択肢（表示順）
+pub const VOTE_OPTIONS: [VoteOption; 3] = [
+    VoteOption {
+        action: "found",
+        label: "営業してる",
+        label_en: "Open",
+        emoji: "🥙",
+    },
+    VoteOption {
+        action: "not_found",
+        label: "いない",
+        label_en: "Not here",
+        emoji: "❌",
+    },
+    VoteOption {
+        action: "sold_out",
+        label: "売り切れた",
+        label_en: "Sold out",
+        emoji: "🚫",
+    },
+];
+
+/// 投票の取り消しを投票履歴に記録する際のアクション名
+/// 履歴から最終的な投票を求める際は、この記録より前の投票を無効にする
+pub const RETRACT_ACTION: &str = "retract";
+
+/// 投票の取り消しの結果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RetractOutcome {
+    /// 取り消した（取り消す前の投票のアクション名）
+    Retracted { old_action: String },
+    /// 現在の期間に投票していない
+    NoVote,
+    /// 連打対策の待ち時間中
+    CoolingDown { remaining_secs: u64 },
+}
+
+impl RetractOutcome {
+    /// 取り消したユーザーに返すメッセージ
+    pub fn message(&self) -> String {
+        match self {
+            RetractOutcome::Retracted { old_action } => format!(
+                "↩️ 「{}」への投票を取り消しました。",
+                VoteService::action_label(old_action)
+            ),
+            RetractOutcome::NoVote => "取り消す投票がありません。".to_string(),
+            RetractOutcome::CoolingDown { remaining_secs } => {
+                format!("⏳ 投票は{}秒後に変更できます。", remaining_secs)
+            }
+        }
+    }
+}
 
 pub struct VoteService;
 
 impl VoteService {
     pub async fn create_vote(
-        db: &DatabaseConnection,
+        db: &impl ConnectionTrait,
         user_id: i64,
         action: String,
     ) -> Result<VoteModel, DbErr> {
-        let now = Utc::now().into();
+        let now = Self::to_db_timestamp(Utc::now());
 
         let vote = vote::ActiveModel {
             user_id: Set(user_id),
@@ -24,25 +99,184 @@ impl VoteService {
         vote.insert(db).await
     }
 
+    #[instrument(skip(db))]
     pub async fn update_vote(
         db: &DatabaseConnection,
         user_id: i64,
+        guild_id: Option<i64>,
         action: String,
     ) -> Result<VoteModel, DbErr> {
-        let now = Utc::now().into();
+        let now = Self::to_db_timestamp(Utc::now());
+
+        // 履歴と現在の投票が食い違わないよう、まとめて書き込む
+        let txn = db.begin().await?;
+
+        // 過去期間との比較用に投票履歴を残す
+        Self::record_vote_history(&txn, user_id, guild_id, action.clone()).await?;
 
         // 既存の投票を取得
-        let existing_vote = vote::Entity::find_by_id(user_id).one(db).await?;
+        let existing_vote = vote::Entity::find_by_id(user_id).one(&txn).await?;
 
         // 既存の投票があれば更新、なければ新規作成
-        if let Some(vote) = existing_vote {
-            let mut vote: vote::ActiveModel = vote.into();
-            vote.action = Set(action);
-            vote.updated_at = Set(now);
-            return vote.update(db).await;
+        let vote = match existing_vote {
+            Some(vote) => {
+                let mut vote: vote::ActiveModel = vote.into();
+                vote.action = Set(action);
+                vote.updated_at = Set(now);
+                vote.update(&txn).await?
+            }
+            None => Self::create_vote(&txn, user_id, action).await?,
+        };
+
+        txn.commit().await?;
+        Ok(vote)
+    }
+
+    /// アクション名の表示名（絵文字付き、未知の値はそのまま）
+    pub fn action_label(action: &str) -> String {
+        match VOTE_OPTIONS.iter().find(|option| option.action == action) {
+            Some(option) => format!("{} {}", option.emoji, option.label),
+            None => action.to_string(),
+        }
+    }
+
+    /// ユーザーの現在の投票を取得
+    pub async fn get_vote(
+        db: &DatabaseConnection,
+        user_id: i64,
+    ) -> Result<Option<VoteModel>, DbErr> {
+        vote::Entity::find_by_id(user_id).one(db).await
+    }
+
+    /// ユーザーの現在の期間の投票を削除し、取り消したことを投票履歴に記録する
+    /// 投票していなかった場合は何もせずに`None`を返す
+    #[instrument(skip(db))]
+    pub async fn retract_vote(
+        db: &DatabaseConnection,
+        user_id: i64,
+        guild_id: Option<i64>,
+    ) -> Result<Option<VoteModel>, DbErr> {
+        let txn = db.begin().await?;
+        let Some(vote) = vote::Entity::find_by_id(user_id).one(&txn).await? else {
+            return Ok(None);
+        };
+
+        Self::record_vote_history(&txn, user_id, guild_id, RETRACT_ACTION.to_string()).await?;
+        vote::Entity::delete_by_id(user_id).exec(&txn).await?;
+        txn.commit().await?;
+        Ok(Some(vote))
+    }
+
+    /// 投票履歴を1件記録する
+    pub async fn record_vote_history(
+        db: &impl ConnectionTrait,
+        user_id: i64,
+        guild_id: Option<i64>,
+        action: String,
+    ) -> Result<vote_history::Model, DbErr> {
+        let now = Utc::now();
+
+        let history = vote_history::ActiveModel {
+            user_id: Set(user_id),
+            guild_id: Set(guild_id),
+            action: Set(action),
+            period_date: Set(Self::get_jst_afternoon_period(now)),
+            created_at: Set(Self::to_db_timestamp(now)),
+            ..Default::default()
+        };
+
+        history.insert(db).await
+    }
+
+    /// 指定した期間範囲（両端を含む）の投票履歴を古い順に取得する
+    /// `guild_id`を指定した場合はそのサーバーで行われた投票のみ
+    pub async fn get_vote_history(
+        db: &DatabaseConnection,
+        start: NaiveDate,
+        end: NaiveDate,
+        guild_id: Option<i64>,
+    ) -> Result<Vec<vote_history::Model>, DbErr> {
+        let mut query = vote_history::Entity::find()
+            .filter(vote_history::Column::PeriodDate.between(start, end))
+            .order_by_asc(vote_history::Column::CreatedAt)
+            .order_by_asc(vote_history::Column::Id);
+        if let Some(guild_id) = guild_id {
+            query = query.filter(vote_history::Column::GuildId.eq(guild_id));
+        }
+        query.all(db).await
+    }
+
+    /// 指定した期間範囲（両端を含む）のユーザーの投票履歴を古い順に取得する
+    pub async fn get_user_vote_history(
+        db: &DatabaseConnection,
+        user_id: i64,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<Vec<vote_history::Model>, DbErr> {
+        vote_history::Entity::find()
+            .filter(vote_history::Column::UserId.eq(user_id))
+            .filter(vote_history::Column::PeriodDate.between(start, end))
+            .order_by_asc(vote_history::Column::CreatedAt)
+            .order_by_asc(vote_history::Column::Id)
+            .all(db)
+            .await
+    }
+
+    /// 指定した投票期間の最終的な投票状態を履歴から復元する
+    /// ユーザーごとに期間内の最後の投票を採用し、`vote`テーブルと同じ形で返す
+    /// 最後に投票を取り消したユーザーは含まない
+    pub async fn get_period_final_votes(
+        db: &DatabaseConnection,
+        period: NaiveDate,
+    ) -> Result<Vec<VoteModel>, DbErr> {
+        let history = vote_history::Entity::find()
+            .filter(vote_history::Column::PeriodDate.eq(period))
+            .order_by_asc(vote_history::Column::CreatedAt)
+            .order_by_asc(vote_history::Column::Id)
+            .all(db)
+            .await?;
+
+        Ok(Self::final_votes_from_history(history))
+    }
+
+    /// 古い順に並んだ1期間分の投票履歴から最終的な投票を求める（更新日時順）
+    /// 記録者が分からない取り込み済みの履歴は誰の投票か区別できないため、1行ずつ別の投票として数える
+    pub fn final_votes_from_history(history: Vec<vote_history::Model>) -> Vec<VoteModel> {
+        let mut latest: HashMap<i64, VoteModel> = HashMap::new();
+        let mut anonymous: Vec<VoteModel> = Vec::new();
+        for entry in history {
+            if entry.user_id == UNKNOWN_REPORTER_ID {
+                if entry.action != RETRACT_ACTION {
+                    anonymous.push(VoteModel {
+                        user_id: entry.user_id,
+                        action: entry.action,
+                        created_at: entry.created_at,
+                        updated_at: entry.created_at,
+                    });
+                }
+                continue;
+            }
+            if entry.action == RETRACT_ACTION {
+                latest.remove(&entry.user_id);
+                continue;
+            }
+            latest
+                .entry(entry.user_id)
+                .and_modify(|vote| {
+                    vote.action = entry.action.clone();
+                    vote.updated_at = entry.created_at;
+                })
+                .or_insert(VoteModel {
+                    user_id: entry.user_id,
+                    action: entry.action,
+                    created_at: entry.created_at,
+                    updated_at: entry.created_at,
+                });
         }
 
-        Self::create_vote(db, user_id, action).await
+        let mut votes: Vec<VoteModel> = latest.into_values().chain(anonymous).collect();
+        votes.sort_by_key(|vote| vote.updated_at);
+        votes
     }
 
     pub async fn get_vote_by_action(
@@ -80,16 +314,111 @@ impl VoteService {
         end_date: chrono::DateTime<chrono::Utc>,
     ) -> Result<Vec<VoteModel>, DbErr> {
         vote::Entity::find()
-            .filter(vote::Column::UpdatedAt.between(start_date, end_date))
+            .filter(vote::Column::UpdatedAt.between(
+                Self::to_db_timestamp(start_date),
+                Self::to_db_timestamp(end_date),
+            ))
             .order_by_asc(vote::Column::UpdatedAt)
             .all(db)
             .await
     }
 
+    #[instrument(skip(db))]
     pub async fn delete_all_vote(db: &DatabaseConnection) -> Result<DeleteResult, DbErr> {
         vote::Entity::delete_many().exec(db).await
     }
 
+    /// 現在の期間に指定したサーバーで投票したユーザーの投票を取得（サーバーの集計に使う）
+    /// 投票テーブルにはサーバーの情報がないため、現在の期間の投票履歴で最後に投票したサーバーで判定する
+    /// 禁止される前に投票していたユーザーも、禁止されている間はこのサーバーの集計に含めない
+    pub async fn get_guild_votes(
+        db: &DatabaseConnection,
+        guild_id: i64,
+    ) -> Result<Vec<VoteModel>, DbErr> {
+        let banned = ModerationService::get_banned_user_ids(db, guild_id, Utc::now()).await?;
+        let user_ids: Vec<i64> = Self::get_guild_voter_ids(db, guild_id)
+            .await?
+            .into_iter()
+            .filter(|user_id| !banned.contains(user_id))
+            .collect();
+        vote::Entity::find()
+            .filter(vote::Column::UserId.is_in(user_ids))
+            .order_by_asc(vote::Column::UpdatedAt)
+            .all(db)
+            .await
+    }
+
+    /// 現在の期間に指定したサーバーで投票したユーザーの票数を選択肢ごとに数える（`VOTE_OPTIONS`の順）
+    pub async fn count_guild_votes(
+        db: &DatabaseConnection,
+        guild_id: i64,
+    ) -> Result<Vec<u64>, DbErr> {
+        let votes = Self::get_guild_votes(db, guild_id).await?;
+        Ok(VOTE_OPTIONS
+            .iter()
+            .map(|option| {
+                votes
+                    .iter()
+                    .filter(|vote| vote.action == option.action)
+                    .count() as u64
+            })
+            .collect())
+    }
+
+    /// 現在の期間に指定したサーバーで投票したユーザーの投票を削除
+    #[instrument(skip(db))]
+    pub async fn delete_guild_votes(
+        db: &DatabaseConnection,
+        guild_id: i64,
+    ) -> Result<DeleteResult, DbErr> {
+        let user_ids = Self::get_guild_voter_ids(db, guild_id).await?;
+        vote::Entity::delete_many()
+            .filter(vote::Column::UserId.is_in(user_ids))
+            .exec(db)
+            .await
+    }
+
+    /// 現在の期間の投票履歴で、最後に指定したサーバーで投票したユーザーのID
+    pub(crate) async fn get_guild_voter_ids(
+        db: &DatabaseConnection,
+        guild_id: i64,
+    ) -> Result<Vec<i64>, DbErr> {
+        Ok(Self::get_latest_voter_guilds(db)
+            .await?
+            .into_iter()
+            .filter(|(_, latest)| *latest == Some(guild_id))
+            .map(|(user_id, _)| user_id)
+            .collect())
+    }
+
+    /// 現在の投票の数をサーバーごとに数える（サーバーの分からない投票は数えない）
+    pub async fn count_votes_by_guild(
+        db: &DatabaseConnection,
+    ) -> Result<BTreeMap<i64, u64>, DbErr> {
+        let latest_guilds = Self::get_latest_voter_guilds(db).await?;
+        let mut counts = BTreeMap::new();
+        for vote in Self::get_all_votes(db).await? {
+            if let Some(Some(guild_id)) = latest_guilds.get(&vote.user_id) {
+                *counts.entry(*guild_id).or_insert(0) += 1;
+            }
+        }
+        Ok(counts)
+    }
+
+    /// 現在の期間の投票履歴で、ユーザーごとに最後に投票したサーバー
+    async fn get_latest_voter_guilds(
+        db: &DatabaseConnection,
+    ) -> Result<HashMap<i64, Option<i64>>, DbErr> {
+        let period = Self::get_current_jst_afternoon_period();
+        let history = Self::get_vote_history(db, period, period, None).await?;
+
+        let mut latest_guild: HashMap<i64, Option<i64>> = HashMap::new();
+        for entry in history {
+            latest_guild.insert(entry.user_id, entry.guild_id);
+        }
+        Ok(latest_guild)
+    }
+
     /// 最新の投票更新日時を取得
     pub async fn get_latest_vote_updated_at(
         db: &DatabaseConnection,
@@ -98,22 +427,63 @@ impl VoteService {
             .order_by_desc(vote::Column::UpdatedAt)
             .one(db)
             .await
-            .map(|vote| vote.map(|v| v.updated_at.naive_utc().and_utc()))
+            .map(|vote| vote.map(|v| v.updated_at.with_timezone(&Utc)))
+    }
+
+    /// 投票データの状態（件数と最新の更新日時）を取得
+    pub async fn get_vote_data_version(db: &DatabaseConnection) -> Result<VoteDataVersion, DbErr> {
+        let count = vote::Entity::find().count(db).await?;
+        let latest_updated_at = Self::get_latest_vote_updated_at(db).await?;
+        Ok(VoteDataVersion {
+            count,
+            latest_updated_at,
+        })
+    }
+
+    /// データベースに保存・比較する日時の形式に変換する
+    /// SQLiteでは日時が文字列として保存・比較されるため、PostgreSQLと同じ結果になるよう常にUTCに揃える
+    pub fn to_db_timestamp(dt: DateTime<Utc>) -> DateTimeWithTimeZone {
+        dt.fixed_offset()
+    }
+
+    /// 投票期間の区切り方を設定する（起動時に一度だけ呼ぶ）
+    pub fn configure_period(config: PeriodConfig) {
+        let _ = PERIOD_CONFIG.set(config);
+    }
+
+    /// 投票期間の区切り方（未設定なら日本時間の正午区切り）
+    fn period_config() -> PeriodConfig {
+        PERIOD_CONFIG
+            .get()
+            .copied()
+            .unwrap_or_else(|| Config::default().period)
+    }
+
+    /// 投票期間を判定するタイムゾーン
+    pub fn period_timezone() -> Tz {
+        Self::period_config().timezone
     }
 
     /// 日本時間での現在の投票期間（午後期間）を取得
     /// 午後12時（正午）から午後11時59分59秒までを1つの投票期間とする
+    /// タイムゾーンと区切りの時刻は設定で変更できる
     pub fn get_current_jst_afternoon_period() -> NaiveDate {
-        let now_jst = Utc::now().with_timezone(&Tokyo);
+        Self::get_jst_afternoon_period(Utc::now())
+    }
+
+    /// 指定した日時が属する日本時間での投票期間（午後期間）を取得
+    pub fn get_jst_afternoon_period(dt: DateTime<Utc>) -> NaiveDate {
+        let config = Self::period_config();
+        let local_dt = dt.with_timezone(&config.timezone);
 
-        // 午後12時（正午）より前の場合は前日の午後期間とみなす
-        if now_jst.hour() < 12 {
-            now_jst
+        // 区切りの時刻（既定は正午）より前の場合は前日の期間とみなす
+        if local_dt.hour() < config.rollover_hour {
+            local_dt
                 .date_naive()
                 .pred_opt()
-                .unwrap_or(now_jst.date_naive())
+                .unwrap_or(local_dt.date_naive())
         } else {
-            now_jst.date_naive()
+            local_dt.date_naive()
         }
     }
 
@@ -121,27 +491,62 @@ impl VoteService {
     pub async fn get_latest_vote_jst_afternoon_period(
         db: &DatabaseConnection,
     ) -> Result<Option<NaiveDate>, DbErr> {
-        if let Some(dt) = Self::get_latest_vote_updated_at(db).await? {
-            let jst_dt = dt.with_timezone(&Tokyo);
-
-            // 午後12時（正午）より前の場合は前日の午後期間とみなす
-            let period_date = if jst_dt.hour() < 12 {
-                jst_dt
-                    .date_naive()
-                    .pred_opt()
-                    .unwrap_or(jst_dt.date_naive())
-            } else {
-                jst_dt.date_naive()
-            };
-
-            Ok(Some(period_date))
-        } else {
-            Ok(None)
+        Ok(Self::get_latest_vote_updated_at(db)
+            .await?
+            .map(Self::get_jst_afternoon_period))
+    }
+
+    /// ボタンやコマンドからの投票の取り消しを処理する
+    /// 連打対策の待ち時間を確認し、取り消した場合はグラフキャッシュ・メトリクス・監査ログに反映する
+    /// 掲示板の更新は返答の後に呼び出し側で行う
+    #[instrument(skip(data))]
+    pub async fn retract_user_vote(
+        data: &crate::Data,
+        user_id: i64,
+        guild_id: Option<i64>,
+    ) -> Result<RetractOutcome, crate::Error> {
+        let cooldown_key = VoteCooldownKey::vote(user_id);
+        if let Err(remaining) = data.vote_cooldown.try_acquire(cooldown_key) {
+            METRICS.record_rate_limited_vote(RETRACT_ACTION);
+            return Ok(RetractOutcome::CoolingDown {
+                remaining_secs: remaining.as_secs_f64().ceil() as u64,
+            });
+        }
+
+        let retracted = match Self::retract_vote(&data.database, user_id, guild_id).await {
+            Ok(retracted) => retracted,
+            Err(e) => {
+                data.vote_cooldown.release(cooldown_key);
+                return Err(e.into());
+            }
+        };
+        let Some(vote) = retracted else {
+            // 何も変わっていないので待ち時間を発生させない
+            data.vote_cooldown.release(cooldown_key);
+            return Ok(RetractOutcome::NoVote);
+        };
+
+        data.chart_cache.invalidate();
+        METRICS.record_vote(RETRACT_ACTION);
+        info!(old_action = %vote.action, "投票を取り消しました");
+        if let Some(guild_id) = guild_id {
+            data.audit_log.record(
+                guild_id,
+                AuditEvent::VoteRetracted {
+                    user_id,
+                    old_action: vote.action.clone(),
+                },
+            );
         }
+
+        Ok(RetractOutcome::Retracted {
+            old_action: vote.action,
+        })
     }
 
     /// 投票期間が変わったかどうかをチェックし、変わっていた場合は投票をリセット
     /// 午後12時（正午）を境に投票期間が切り替わる
+    #[instrument(skip(db))]
     pub async fn check_and_reset_votes_if_new_day(db: &DatabaseConnection) -> Result<bool, DbErr> {
         let current_period = Self::get_current_jst_afternoon_period();
         let latest_vote_period = Self::get_latest_vote_jst_afternoon_period(db).await?;
@@ -150,15 +555,17 @@ impl VoteService {
             Some(latest_period) if latest_period < current_period => {
                 // 投票期間が変わっているので投票をリセット
                 Self::delete_all_vote(db).await?;
-                println!(
-                    "🔄 投票期間が変わったため投票をリセットしました: {} → {}",
-                    latest_period, current_period
+                METRICS.record_rollover();
+                info!(
+                    %latest_period,
+                    %current_period,
+                    "投票期間が変わったため投票をリセットしました"
                 );
                 Ok(true)
             }
             None => {
                 // 投票データがない場合（初回起動など）
-                println!("ℹ️ 投票データがありません（初回起動または既にリセット済み）");
+                info!("投票データがありません（初回起動または既にリセット済み）");
                 Ok(false)
             }
             Some(_) => {
@@ -170,41 +577,65 @@ impl VoteService {
 
     /// 投票期間が変わったかどうかをチェックし、変わっていた場合は投票をリセットして掲示板を更新
     /// Serenity Contextを使用して掲示板の更新も行う
+    #[instrument(skip_all)]
     pub async fn check_reset_and_update_board_if_new_day(
-        db: &DatabaseConnection,
+        data: &crate::Data,
         serenity_ctx: &poise::serenity_prelude::Context,
-    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+    ) -> Result<bool, crate::Error> {
+        let db = data.database.as_ref();
         let reset = Self::check_and_reset_votes_if_new_day(db).await?;
 
         if reset {
+            data.chart_cache.invalidate();
+
             // 投票期間が変わった場合、掲示板も更新する
             let board_data = crate::services::BoardService::get_all_board_data(db).await?;
             if !board_data.is_empty() {
-                println!("📋 投票期間変更に伴い掲示板を更新中...");
-
-                // タイムラインチャートを再生成（空のデータで）
-                let timeline_path = "vote_timeline.png";
-                let votes = Self::get_all_votes(db).await?;
-                if let Err(e) = crate::services::ChartService::generate_vote_timeline_chart(
-                    votes,
-                    timeline_path,
-                )
-                .await
-                {
-                    eprintln!("タイムラインチャート生成エラー: {}", e);
-                }
+                info!(
+                    boards = board_data.len(),
+                    "投票期間変更に伴い掲示板を更新します"
+                );
 
                 let _response =
                     crate::services::BoardUIService::update_all_board_messages_serenity(
                         serenity_ctx,
                         board_data,
-                        db,
+                        data,
                     )
                     .await?;
-                println!("✅ 投票期間変更に伴う掲示板更新が完了しました");
+                info!("投票期間変更に伴う掲示板更新が完了しました");
             }
         }
 
         Ok(reset)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::test_support::history;
+
+    #[test]
+    fn final_votes_count_each_anonymous_row() {
+        let votes = VoteService::final_votes_from_history(vec![
+            history(1, UNKNOWN_REPORTER_ID, "found", 0),
+            history(2, 42, "not_found", 1),
+            history(3, UNKNOWN_REPORTER_ID, "found", 2),
+            history(4, 42, "found", 3),
+        ]);
+
+        let actions: Vec<(i64, &str)> = votes
+            .iter()
+            .map(|vote| (vote.user_id, vote.action.as_str()))
+            .collect();
+        assert_eq!(
+            actions,
+            vec![
+                (UNKNOWN_REPORTER_ID, "found"),
+                (UNKNOWN_REPORTER_ID, "found"),
+                (42, "found"),
+            ]
+        );
+    }
+}
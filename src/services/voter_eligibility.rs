@@ -0,0 +1,188 @@
+use crate::entities::guild_settings::Model as GuildSettingsModel;
+use chrono::{DateTime, TimeDelta, Utc};
+use poise::serenity_prelude::{Member, Timestamp};
+
+/// サーバーごとの投票できるユーザーの条件（設定されていない条件は確認しない）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct VoteEligibility {
+    /// 投票に必要なロール
+    pub required_role_id: Option<i64>,
+    /// Discordのアカウントを作成してから必要な日数
+    pub min_account_age_days: Option<u32>,
+    /// サーバーに参加してから必要な日数
+    pub min_membership_days: Option<u32>,
+}
+
+/// 投票する側のユーザーの情報
+#[derive(Debug, Clone)]
+pub struct Voter {
+    pub role_ids: Vec<i64>,
+    /// ユーザーIDのsnowflakeから求めたアカウントの作成日時
+    pub account_created_at: DateTime<Utc>,
+    /// サーバーに参加した日時（取得できなければ`None`）
+    pub joined_at: Option<DateTime<Utc>>,
+}
+
+impl Voter {
+    /// 投票の条件の確認に使うメンバーの情報を取り出す
+    pub fn from_member(member: &Member) -> Self {
+        let to_utc = |timestamp: Timestamp| {
+            DateTime::from_timestamp(timestamp.unix_timestamp(), 0).unwrap_or_default()
+        };
+        Self {
+            role_ids: member.roles.iter().map(|role| role.get() as i64).collect(),
+            account_created_at: to_utc(member.user.id.created_at()),
+            joined_at: member.joined_at.map(to_utc),
+        }
+    }
+}
+
+/// 投票できない理由
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ineligibility {
+    MissingRole {
+        role_id: i64,
+    },
+    AccountTooNew {
+        required_days: u32,
+        remaining: TimeDelta,
+    },
+    MembershipTooShort {
+        required_days: u32,
+        remaining: TimeDelta,
+    },
+    /// サーバーへの参加日時が分からず、参加日数を確認できない
+    UnknownMembership {
+        required_days: u32,
+    },
+}
+
+impl VoteEligibility {
+    /// サーバー設定から条件を取り出す（設定がなければ条件なし）
+    pub fn from_settings(settings: Option<&GuildSettingsModel>) -> Self {
+        let Some(settings) = settings else {
+            return Self::default();
+        };
+        let days = |days: Option<i32>| {
+            days.and_then(|days| u32::try_from(days).ok())
+                .filter(|days| *days > 0)
+        };
+        Self {
+            required_role_id: settings.vote_required_role_id,
+            min_account_age_days: days(settings.vote_min_account_age_days),
+            min_membership_days: days(settings.vote_min_membership_days),
+        }
+    }
+
+    /// 条件が1つも設定されていないか
+    pub fn is_unrestricted(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// ユーザーが全ての条件を満たしているか確認し、満たしていなければ最初に見つかった理由を返す
+    pub fn check(&self, voter: &Voter, now: DateTime<Utc>) -> Result<(), Ineligibility> {
+        if let Some(role_id) = self.required_role_id
+            && !voter.role_ids.contains(&role_id)
+        {
+            return Err(Ineligibility::MissingRole { role_id });
+        }
+
+        if let Some(required_days) = self.min_account_age_days {
+            let remaining = voter.account_created_at + TimeDelta::days(required_days as i64) - now;
+            if remaining > TimeDelta::zero() {
+                return Err(Ineligibility::AccountTooNew {
+                    required_days,
+                    remaining,
+                });
+            }
+        }
+
+        if let Some(required_days) = self.min_membership_days {
+            let Some(joined_at) = voter.joined_at else {
+                return Err(Ineligibility::UnknownMembership { required_days });
+            };
+            let remaining = joined_at + TimeDelta::days(required_days as i64) - now;
+            if remaining > TimeDelta::zero() {
+                return Err(Ineligibility::MembershipTooShort {
+                    required_days,
+                    remaining,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 条件の一覧を表示用の文字列にする
+    pub fn describe(&self) -> String {
+        if self.is_unrestricted() {
+            return "条件なし（誰でも投票できます）".to_string();
+        }
+
+        let mut lines = Vec::new();
+        if let Some(role_id) = self.required_role_id {
+            lines.push(format!("必要なロール: <@&{}>", role_id));
+        }
+        if let Some(days) = self.min_account_age_days {
+            lines.push(format!("アカウント作成からの日数: {}日以上", days));
+        }
+        if let Some(days) = self.min_membership_days {
+            lines.push(format!("サーバー参加からの日数: {}日以上", days));
+        }
+        lines.join("\n")
+    }
+}
+
+impl Ineligibility {
+    /// 投票を断る際にユーザーに表示するメッセージ
+    pub fn message(&self) -> String {
+        match self {
+            Ineligibility::MissingRole { role_id } => {
+                format!("🚫 投票するには <@&{}> ロールが必要です。", role_id)
+            }
+            Ineligibility::AccountTooNew {
+                required_days,
+                remaining,
+            } => format!(
+                "🚫 作成から{}日以上経ったアカウントのみ投票できます（あと{}）。",
+                required_days,
+                Self::format_remaining(*remaining)
+            ),
+            Ineligibility::MembershipTooShort {
+                required_days,
+                remaining,
+            } => format!(
+                "🚫 サーバーに参加してから{}日以上経つと投票できます（あと{}）。",
+                required_days,
+                Self::format_remaining(*remaining)
+            ),
+            Ineligibility::UnknownMembership { required_days } => format!(
+                "🚫 サーバーに参加してから{}日以上経ったメンバーのみ投票できますが、参加日を確認できませんでした。",
+                required_days
+            ),
+        }
+    }
+
+    /// ログやメトリクスに使う理由の名前
+    pub fn code(&self) -> &'static str {
+        match self {
+            Ineligibility::MissingRole { .. } => "missing_role",
+            Ineligibility::AccountTooNew { .. } => "account_age",
+            Ineligibility::MembershipTooShort { .. } | Ineligibility::UnknownMembership { .. } => {
+                "membership"
+            }
+        }
+    }
+
+    /// 残り時間を日・時間・分のいずれかで切り上げて表す
+    fn format_remaining(remaining: TimeDelta) -> String {
+        let minutes = (remaining.num_seconds().max(0) as u64).div_ceil(60);
+        if minutes >= 24 * 60 {
+            format!("{}日", minutes.div_ceil(24 * 60))
+        } else if minutes >= 60 {
+            format!("{}時間", minutes.div_ceil(60))
+        } else {
+            format!("{}分", minutes.max(1))
+        }
+    }
+}
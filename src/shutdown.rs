@@ -0,0 +1,95 @@
+//! 終了シグナルを受け取った後に処理中の作業を待ってから停止するための状態
+
+use std::future::Future;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+use tokio_util::task::TaskTracker;
+use tokio_util::task::task_tracker::TaskTrackerToken;
+use tracing::{info, warn};
+
+/// 停止の開始を知らせ、処理中の作業（ボタン操作・コマンド・定期チェック）を数える
+#[derive(Clone, Default)]
+pub struct Shutdown {
+    token: CancellationToken,
+    tracker: TaskTracker,
+}
+
+impl Shutdown {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 停止処理が始まっていて、新しい操作を受け付けない状態か
+    pub fn is_shutting_down(&self) -> bool {
+        self.token.is_cancelled()
+    }
+
+    /// 停止処理が始まるまで待つ
+    pub async fn cancelled(&self) {
+        self.token.cancelled().await
+    }
+
+    /// 作業を処理中として数えながら実行する
+    pub async fn track<F: Future>(&self, future: F) -> F::Output {
+        self.tracker.track_future(future).await
+    }
+
+    /// 破棄されるまで処理中として数えるトークン（コマンドの実行中に保持する）
+    pub fn in_flight(&self) -> TaskTrackerToken {
+        self.tracker.token()
+    }
+
+    /// 停止処理を始め、処理中の作業が終わるまで最大`timeout`待つ
+    /// 時間内に全て終わった場合は`true`
+    pub async fn drain(&self, timeout: Duration) -> bool {
+        self.token.cancel();
+        self.tracker.close();
+
+        info!(
+            in_flight = self.tracker.len(),
+            "処理中の作業の完了を待っています"
+        );
+        match tokio::time::timeout(timeout, self.tracker.wait()).await {
+            Ok(()) => true,
+            Err(_) => {
+                warn!(
+                    in_flight = self.tracker.len(),
+                    timeout_secs = timeout.as_secs(),
+                    "処理中の作業が時間内に終わりませんでした"
+                );
+                false
+            }
+        }
+    }
+}
+
+/// SIGTERM（Unixのみ）またはCtrl+C（SIGINT）を受け取るまで待ち、受け取ったシグナルの名前を返す
+pub async fn wait_for_signal() -> &'static str {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            warn!(error = %e, "Ctrl+Cを待ち受けられません");
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        use tokio::signal::unix::{SignalKind, signal};
+        match signal(SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(e) => {
+                warn!(error = %e, "SIGTERMを待ち受けられません");
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => "SIGINT",
+        _ = terminate => "SIGTERM",
+    }
+}